@@ -0,0 +1,44 @@
+//! Prints hand histories in the PokerStars text format.
+//!
+//! Reads the JSON answer of a `GetHandHistory` query, or a single hand from it, from the file
+//! given as the first argument or from stdin:
+//!
+//!     secretcli q compute query $CONTRACT '{"get_hand_history":{}}' | cargo run --bin pokerstars
+
+use std::io::Read;
+use std::{env, fs, io, process};
+
+use secret_holdem::contract::{HandHistory, HandHistoryPage};
+use secret_holdem::pokerstars::export_hand;
+use serde_json_wasm as serde_json;
+
+fn main() {
+    let input = match env::args().nth(1) {
+        Some(path) => fs::read(&path).unwrap_or_else(|err| {
+            eprintln!("Can't read {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => {
+            let mut input = vec![];
+            io::stdin().read_to_end(&mut input).unwrap_or_else(|err| {
+                eprintln!("Can't read stdin: {}", err);
+                process::exit(1);
+            });
+            input
+        }
+    };
+
+    let hands = match serde_json::from_slice::<HandHistoryPage>(&input) {
+        Ok(page) => page.hands,
+        Err(_) => match serde_json::from_slice::<HandHistory>(&input) {
+            Ok(hand) => vec![hand],
+            Err(err) => {
+                eprintln!("Not a hand history: {}", err);
+                process::exit(1);
+            }
+        },
+    };
+
+    let hands: Vec<String> = hands.iter().map(export_hand).collect();
+    println!("{}", hands.join("\n\n\n"));
+}
@@ -0,0 +1,200 @@
+//! Plays a scripted game against mock storage, without a node.
+//!
+//!     cargo run --bin simulate -- scenarios/heads_up.json
+//!
+//! A scenario creates the room with `init` and runs its `steps` in order. Every step runs in a
+//! new block, `wait` skips blocks to let the action timeout run out:
+//!
+//!     {"join": {"sender": "alice", "secret": "at least 16 bytes", "deposit": 1000, "table_id": 2}}
+//!     {"rematch": {"sender": "alice", "secret": "a fresh secret for every hand"}}
+//!     {"reveal": {"sender": "alice", "secret": "at least 16 bytes"}}
+//!     {"execute": {"sender": "alice", "msg": {"raise": {"amount": "20"}}, "deposit": null}}
+//!     {"query": {"msg": {"get_hand_history": {}}}}
+//!     {"wait": {"blocks": 100}}
+//!
+//! Joins and rematches commit to the SHA-256 of their secret, joins without a `table_id` sit at
+//! table 1. The sender's table is printed after every step that changes it.
+
+use std::collections::HashMap;
+use std::{env, fs, process};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{coins, Binary, HandleResponse, StdResult};
+use secret_holdem::contract::{handle, init, query, HandleMsg, InitMsg, QueryMsg};
+use serde::Deserialize;
+use serde_json_wasm as serde_json;
+use sha2::{Digest, Sha256};
+
+const FIRST_BLOCK: u64 = 12_345;
+const BLOCK_TIME: u64 = 6;
+
+#[derive(Deserialize)]
+struct Scenario {
+    init: InitMsg,
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Step {
+    Join {
+        sender: String,
+        secret: String,
+        deposit: u64,
+        table_id: Option<u64>,
+    },
+    Rematch {
+        sender: String,
+        secret: String,
+    },
+    Reveal {
+        sender: String,
+        secret: String,
+    },
+    Execute {
+        sender: String,
+        msg: HandleMsg,
+        deposit: Option<u64>,
+    },
+    Query {
+        msg: QueryMsg,
+    },
+    Wait {
+        blocks: u64,
+    },
+}
+
+fn commitment(secret: &str) -> Binary {
+    Binary(Sha256::digest(secret.as_bytes()).to_vec())
+}
+
+/// Binary answers of the contract are JSON, print them as such.
+fn json(data: &Binary) -> String {
+    String::from_utf8_lossy(data.as_slice()).into_owned()
+}
+
+fn print_response(result: StdResult<HandleResponse>) -> bool {
+    match result {
+        Ok(response) => {
+            for attribute in response.log.iter() {
+                println!("  {}: {}", attribute.key, attribute.value);
+            }
+            for message in response.messages.iter() {
+                println!("  message: {:?}", message);
+            }
+            if let Some(data) = response.data {
+                println!("  data: {}", json(&data));
+            }
+            true
+        }
+        Err(err) => {
+            println!("  error: {}", err);
+            false
+        }
+    }
+}
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("Usage: simulate <scenario.json>");
+        process::exit(1);
+    });
+    let scenario = fs::read(&path).unwrap_or_else(|err| {
+        eprintln!("Can't read {}: {}", path, err);
+        process::exit(1);
+    });
+    let scenario: Scenario = serde_json::from_slice(&scenario).unwrap_or_else(|err| {
+        eprintln!("Not a scenario: {}", err);
+        process::exit(1);
+    });
+
+    let mut deps = mock_dependencies(20, &[]);
+    let mut height = FIRST_BLOCK;
+    let mut tables: HashMap<String, u64> = HashMap::new();
+    let block = |sender: &str, deposit: Option<u64>, height: u64| {
+        let funds = match deposit {
+            Some(amount) => coins(amount as u128, "uscrt"),
+            None => vec![],
+        };
+        let mut env = mock_env(sender, &funds);
+        env.block.height = height;
+        env.block.time = height * BLOCK_TIME;
+        env
+    };
+
+    println!("init: {}", serde_json::to_string(&scenario.init).unwrap());
+    if let Err(err) = init(&mut deps, block("creator", None, height), scenario.init) {
+        println!("  error: {}", err);
+        process::exit(1);
+    }
+
+    for (i, step) in scenario.steps.into_iter().enumerate() {
+        height += 1;
+
+        let (sender, msg, deposit) = match step {
+            Step::Join {
+                sender,
+                secret,
+                deposit,
+                table_id,
+            } => (
+                sender,
+                HandleMsg::JoinTable {
+                    table_id: table_id.unwrap_or(1),
+                    commitment: commitment(&secret),
+                },
+                Some(deposit),
+            ),
+            Step::Rematch { sender, secret } => (
+                sender,
+                HandleMsg::Rematch {
+                    commitment: commitment(&secret),
+                },
+                None,
+            ),
+            Step::Reveal { sender, secret } => (
+                sender,
+                HandleMsg::Reveal {
+                    secret: Binary(secret.into_bytes()),
+                },
+                None,
+            ),
+            Step::Execute {
+                sender,
+                msg,
+                deposit,
+            } => (sender, msg, deposit),
+            Step::Query { msg } => {
+                println!("#{} query: {}", i + 1, serde_json::to_string(&msg).unwrap());
+                match query(&deps, msg) {
+                    Ok(answer) => println!("  {}", json(&answer)),
+                    Err(err) => println!("  error: {}", err),
+                }
+                continue;
+            }
+            Step::Wait { blocks } => {
+                height += blocks.saturating_sub(1);
+                println!("#{} wait until block {}", i + 1, height);
+                continue;
+            }
+        };
+
+        println!(
+            "#{} block {}, {}: {}",
+            i + 1,
+            height,
+            sender,
+            serde_json::to_string(&msg).unwrap()
+        );
+        if let HandleMsg::JoinTable { table_id, .. } = &msg {
+            tables.insert(sender.clone(), *table_id);
+        }
+        let result = handle(&mut deps, block(&sender, deposit, height), msg);
+        if print_response(result) {
+            let table_id = tables.get(&sender).cloned();
+            if let Ok(table) = query(&deps, QueryMsg::GetPublicData { table_id }) {
+                println!("  table: {}", json(&table));
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use cosmwasm_std::{
-    Api, BankMsg, Binary, Coin, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr,
-    InitResponse, InitResult, Querier, QueryResult, StdError, StdResult, Storage, Uint128,
+    from_binary, log, to_binary, Api, BankMsg, Binary, Coin, CosmosMsg, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, LogAttribute, Querier,
+    QueryResult, StdResult, Storage, Uint128,
 };
 use rand::{seq::SliceRandom, SeedableRng};
 use rand_chacha::ChaChaRng;
@@ -10,104 +11,349 @@ use serde::{Deserialize, Serialize};
 use serde_json_wasm as serde_json;
 use sha2::{Digest, Sha256};
 
+use crate::error::ContractError;
+use crate::snip20::{register_receive_msg, transfer_msg};
+use crate::state::{
+    deck_to_bytes, load_commitment, load_deck, load_history, load_lobby, load_proof,
+    load_raked_tables, load_secret, load_table, player_table, player_table_id, remove_secret,
+    save_commitment, save_deck, save_history, save_lobby, save_prng_seed, save_proof,
+    save_raked_tables, save_secret, save_table, seat_player, unseat_player,
+};
+use crate::viewing_key::{check_viewing_key, create_viewing_key, set_viewing_key};
+
 #[derive(Serialize, Deserialize, Clone)]
-struct Table {
+pub(crate) struct Table {
+    pub(crate) id: u64, // a room created with CreateRoom is table 1
     game_counter: u64,
 
-    player_a: Option<HumanAddr>,
-    player_a_wallet: i64,
-    player_a_bet: u64,
+    max_seats: u8,
+    players: Vec<Player>, // ordered by position
 
-    player_b: Option<HumanAddr>,
-    player_b_wallet: i64,
-    player_b_bet: u64,
-
-    starter: Option<HumanAddr>,
-    turn: Option<HumanAddr>, // round ends if after a bet: starter != turn && player_a_bet == player_b_bet or if someone called
+    button: Option<u8>, // position of the button, heads-up the button posts the small blind, otherwise the next two seats post the blinds
+    turn: Option<HumanAddr>, // round ends when every player that can still bet has acted and matched the highest bet
     last_play: Option<String>,
 
     stage: Stage,
 
     community_cards: Vec<Card>,
+    dealt: u8, // players dealt into the current hand, the board comes after their hole cards
+    deck_commitment: Option<Binary>, // published when the hand is dealt, see HandProof
 
-    player_a_hand: Vec<Card>,
-    player_b_hand: Vec<Card>,
-
-    player_a_wants_rematch: bool,
-    player_b_wants_rematch: bool,
+    hand_seats: Vec<HandSeat>, // players dealt into the current hand and their starting stacks
+    hand_actions: Vec<Event>,  // everything that happened since the current hand was dealt
 
-    player_a_win_counter: u64,
-    player_b_win_counter: u64,
+    winners: Vec<HumanAddr>, // winners of the last hand, more than one means the pot was split
+    pots: Vec<Pot>,          // how the pot of the last hand was divided, main pot first
     tie_counter: u64,
 
-    max_credit: u64,
-    min_credit: u64,
-    big_blind: u64,
+    max_credit: Uint128,
+    min_credit: Uint128,
+    max_buy_in: u32, // max_credit in big blinds
+    min_buy_in: u32, // min_credit in big blinds
+    big_blind: Uint128,
+    small_blind: Uint128,
+    last_raise: Uint128, // size of the last full raise on this street, the minimum for the next one
+    street_bets: u8,     // bets and raises made on this street, fixed-limit caps it
+    betting_structure: BettingStructure,
+    currency: Currency,
+    rake: Option<Rake>,
+    uncollected_rake: Uint128, // raked from pots since the collector last collected
+
+    action_timeout: u64, // blocks the player to act has before the others can claim a timeout
+    last_action_height: u64,
+    last_action_time: u64,
+
+    #[serde(skip)]
+    log: Vec<LogAttribute>, // events of the current transaction, never stored
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Player {
+    address: HumanAddr,
+    position: u8,
+    wallet: Uint128,
+    bet: Uint128,       // bet on the current street
+    committed: Uint128, // total put into the pot in the current hand, including the current street
+
+    hand: Vec<Card>, // only revealed at showdown
+
+    in_hand: bool,          // was dealt into the current hand
+    deal_index: Option<u8>, // deck index of the first hole card, the second one is `dealt` cards later
+    revealed: bool,         // revealed the secret committed to for the current hand
+    folded: bool,
+    acted: bool, // acted since the last full raise on this street
+
+    wants_rematch: bool, // committed to a fresh secret for the next hand
+    sitting_out: bool,   // didn't approve a hand in time, dealt in again after sending Rematch
+    win_counter: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Pot {
+    pub amount: Uint128,
+    pub rake: Uint128, // taken from the amount before it was paid to the winners
+    pub eligible: Vec<HumanAddr>, // players that put enough into the pot to win it
+    pub winners: Vec<HumanAddr>,
+}
+
+/// The tables hosted by this contract.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Lobby {
+    open: bool,         // anyone can create tables, a room created with CreateRoom is closed
+    table_count: u64,   // tables are numbered from 1
+    rake: Option<Rake>, // taken at every table
 }
 
-// struct Player {
-//     address: Option<HumanAddr>,
-//     position: u8,
-//     wallet: u128,
-//     current_bet: i64
-// }
+impl Player {
+    /// Still has a claim on the pot.
+    fn is_contending(&self) -> bool {
+        self.in_hand && !self.folded
+    }
+
+    /// Still has a claim on the pot and chips left to bet with.
+    fn can_act(&self) -> bool {
+        self.is_contending() && !self.wallet.is_zero()
+    }
+}
 
 /////////////////////////////// Init ///////////////////////////////
 //
 ////////////////////////////////////////////////////////////////////
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BettingStructure {
+    /// Raise by any amount from the minimum raise up to your whole wallet
+    NoLimit,
+    /// Raise by at most the size of the pot after calling
+    PotLimit,
+    /// Raise by exactly the small bet (pre-flop and flop) or the big bet (turn and river),
+    /// with a cap on the number of bets per street
+    FixedLimit,
+}
+
+/// What players buy in with, credits are counted in its smallest unit.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Currency {
+    /// A native coin, sent along with `Join`, `JoinTable` and `TopUp`
+    Native { denom: String },
+    /// A SNIP-20 token, sent with the token's `Send` and one of these messages as its `msg`
+    Snip20 {
+        address: HumanAddr,
+        code_hash: String,
+    },
+}
+
+impl Currency {
+    fn name(&self) -> String {
+        match self {
+            Self::Native { denom } => denom.clone(),
+            Self::Snip20 { address, .. } => address.to_string(),
+        }
+    }
+}
+
+/// The house's cut of the pots, kept by the contract until the collector sends `CollectRake`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Rake {
+    pub basis_points: u16,     // of every pot, 500 takes 5%
+    pub cap: Option<u32>,      // per hand, in big blinds
+    pub no_flop_no_drop: bool, // hands that end before the flop aren't raked
+    pub collector: HumanAddr,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum InitMsg {
-    CreateRoom { big_blind: u64 },
+    CreateRoom {
+        big_blind: Uint128,
+        betting_structure: Option<BettingStructure>, // defaults to no-limit
+        max_seats: Option<u8>,                       // defaults to heads-up
+        action_timeout: Option<u64>, // in blocks, defaults to DEFAULT_ACTION_TIMEOUT
+        min_buy_in: Option<u32>,     // in big blinds, defaults to DEFAULT_MIN_BUY_IN
+        max_buy_in: Option<u32>,     // in big blinds, defaults to DEFAULT_MAX_BUY_IN
+        currency: Option<Currency>,  // defaults to DEFAULT_DENOM
+        rake: Option<Rake>,          // defaults to no rake
+        prng_seed: Binary,           // entropy for generating viewing keys
+    },
+    // starts without tables, anyone can add them with CreateTable
+    CreateLobby {
+        rake: Option<Rake>,
+        prng_seed: Binary,
+    },
 }
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> InitResult {
-    match msg {
-        InitMsg::CreateRoom { big_blind } => {
-            let table = Table {
-                game_counter: 0,
+    // nothing can be deposited before joining a table
+    let mut messages = vec![];
+    messages.extend(refund_msg(&env, env.message.sent_funds.clone()));
+    let (lobby, prng_seed) = match msg {
+        InitMsg::CreateRoom {
+            big_blind,
+            betting_structure,
+            max_seats,
+            action_timeout,
+            min_buy_in,
+            max_buy_in,
+            currency,
+            rake,
+            prng_seed,
+        } => {
+            check_rake(&deps.api, &rake)?;
+            let settings = TableSettings {
+                big_blind,
+                betting_structure,
+                max_seats,
+                action_timeout,
+                min_buy_in,
+                max_buy_in,
+                currency,
+                rake: rake.clone(),
+            };
+            let table = new_table(DEFAULT_TABLE_ID, &env, settings)?;
+            save_table(&mut deps.storage, &table)?;
+            messages.extend(table.register_receive(&env)?);
+
+            let lobby = Lobby {
+                open: false,
+                table_count: 1,
+                rake,
+            };
+            (lobby, prng_seed)
+        }
+        InitMsg::CreateLobby { rake, prng_seed } => {
+            check_rake(&deps.api, &rake)?;
+            let lobby = Lobby {
+                open: true,
+                table_count: 0,
+                rake,
+            };
+            (lobby, prng_seed)
+        }
+    };
+    save_lobby(&mut deps.storage, &lobby)?;
 
-                player_a: None,
-                player_b: None,
+    // the creator's entropy, mixed with the block so a reused seed still differs
+    let mut seed_input = prng_seed.as_slice().to_vec();
+    seed_input.extend(env.contract.address.as_str().as_bytes());
+    seed_input.extend(&env.block.height.to_be_bytes());
+    seed_input.extend(&env.block.time.to_be_bytes());
+    save_prng_seed(&mut deps.storage, Sha256::digest(&seed_input).as_slice());
 
-                player_a_wallet: 0,
-                player_b_wallet: 0,
+    Ok(InitResponse {
+        messages,
+        log: vec![],
+    })
+}
 
-                player_a_bet: 0,
-                player_b_bet: 0,
+/// The settings of `CreateRoom` and `CreateTable`.
+struct TableSettings {
+    big_blind: Uint128,
+    betting_structure: Option<BettingStructure>,
+    max_seats: Option<u8>,
+    action_timeout: Option<u64>,
+    min_buy_in: Option<u32>,
+    max_buy_in: Option<u32>,
+    currency: Option<Currency>,
+    rake: Option<Rake>, // the contract's, tables can't set their own
+}
 
-                stage: Stage::WaitingForPlayersToJoin,
-                starter: None,
-                turn: None,
-                last_play: None,
+fn check_rake<A: Api>(api: &A, rake: &Option<Rake>) -> StdResult<()> {
+    let rake = match rake {
+        Some(rake) => rake,
+        None => return Ok(()),
+    };
+    if rake.basis_points > MAX_RAKE_BASIS_POINTS {
+        return Err(ContractError::InvalidRake {
+            max: MAX_RAKE_BASIS_POINTS,
+        }
+        .into());
+    }
+    // the rake is paid out to the collector, so it has to be a valid address
+    api.canonical_address(&rake.collector)?;
+    Ok(())
+}
 
-                community_cards: vec![],
+/// A table with nobody seated yet, after checking its settings.
+fn new_table(id: u64, env: &Env, settings: TableSettings) -> StdResult<Table> {
+    let big_blind = settings.big_blind;
+    // the small blind is half the big blind, so it has to split evenly
+    if big_blind.u128() < 2 || big_blind.u128() % 2 == 1 {
+        return Err(ContractError::InvalidBigBlind.into());
+    }
 
-                player_a_hand: vec![],
-                player_b_hand: vec![],
+    let max_seats = settings.max_seats.unwrap_or(MIN_SEATS);
+    if !(MIN_SEATS..=MAX_SEATS).contains(&max_seats) {
+        return Err(ContractError::InvalidSeats {
+            min: MIN_SEATS,
+            max: MAX_SEATS,
+        }
+        .into());
+    }
 
-                player_a_wants_rematch: false,
-                player_b_wants_rematch: false,
+    let action_timeout = settings.action_timeout.unwrap_or(DEFAULT_ACTION_TIMEOUT);
+    if action_timeout == 0 {
+        return Err(ContractError::InvalidActionTimeout.into());
+    }
 
-                player_a_win_counter: 0,
-                player_b_win_counter: 0,
-                tie_counter: 0,
-                max_credit: big_blind * MAX_TABLE_BIG_BLINDS,
-                min_credit: big_blind * MIN_TABLE_BIG_BLINDS,
-                big_blind,
-            };
+    // a stack must at least cover the big blind to be dealt in
+    let min_buy_in = settings.min_buy_in.unwrap_or(DEFAULT_MIN_BUY_IN);
+    let max_buy_in = settings.max_buy_in.unwrap_or(DEFAULT_MAX_BUY_IN);
+    if min_buy_in == 0 || min_buy_in > max_buy_in {
+        return Err(ContractError::InvalidBuyIn.into());
+    }
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+    Ok(Table {
+        id,
+        game_counter: 0,
+
+        max_seats,
+        players: vec![],
+
+        stage: Stage::WaitingForPlayersToJoin,
+        button: None,
+        turn: None,
+        last_play: None,
+
+        community_cards: vec![],
+        dealt: 0,
+        deck_commitment: None,
+
+        hand_seats: vec![],
+        hand_actions: vec![],
+
+        winners: vec![],
+        pots: vec![],
+        tie_counter: 0,
+
+        max_credit: mul_chips(big_blind, max_buy_in as u128)?,
+        min_credit: mul_chips(big_blind, min_buy_in as u128)?,
+        max_buy_in,
+        min_buy_in,
+        big_blind,
+        small_blind: Uint128(big_blind.u128() / 2),
+        last_raise: big_blind,
+        street_bets: 0,
+        betting_structure: settings
+            .betting_structure
+            .unwrap_or(BettingStructure::NoLimit),
+        currency: settings.currency.unwrap_or(Currency::Native {
+            denom: String::from(DEFAULT_DENOM),
+        }),
+        rake: settings.rake,
+        uncollected_rake: Uint128::zero(),
+
+        action_timeout,
+        last_action_height: env.block.height,
+        last_action_time: env.block.time,
 
-            Ok(InitResponse::default())
-        }
-    }
+        log: vec![],
+    })
 }
 
 /////////////////////////////// Handle ///////////////////////////////
@@ -116,107 +362,344 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[repr(u8)]
-enum Stage {
+pub enum Stage {
     WaitingForPlayersToJoin,
+    WaitingForSecrets,
     PreFlop,
     Flop,
     Turn,
     River,
-    EndedWinnerA,
-    EndedWinnerB,
-    EndedDraw,
+    Ended,
 }
 
 impl Stage {
     fn no_more_action(&self) -> bool {
-        match &self {
-            Self::EndedWinnerA
-            | Self::EndedWinnerB
-            | Self::EndedDraw
-            | Self::WaitingForPlayersToJoin => true,
-            _ => false,
-        }
-    }
-
-    fn next_round(&self) -> Self {
-        match &self {
-            Self::PreFlop => Self::Flop,
-            Self::Flop => Self::Turn,
-            Self::Turn => Self::River,
-            _ => Self::PreFlop,
-        }
-    }
-}
-
-const MAX_TABLE_BIG_BLINDS: u64 = 100;
-const MIN_TABLE_BIG_BLINDS: u64 = 20;
-// indexes of cards in the deck
-const PLAYER_A_FIRST_CARD: usize = 0;
-const PLAYER_B_FIRST_CARD: usize = 1;
-const PLAYER_A_SECOND_CARD: usize = 2;
-const PLAYER_B_SECOND_CARD: usize = 3;
-// Pre-flop betting round - burn index 4
-const FLOP_FIRST_CARD: usize = 5;
-const FLOP_SECOND_CARD: usize = 6;
-const FLOP_THIRD_CARD: usize = 7;
-// Flop betting round - burn index 8
-const TURN_CARD: usize = 9;
-// Turn betting round - burn index 10
-const RIVER_CARD: usize = 11;
+        matches!(self, Self::Ended | Self::WaitingForPlayersToJoin)
+    }
+}
+
+// buy-ins, in big blinds
+const DEFAULT_MAX_BUY_IN: u32 = 100;
+const DEFAULT_MIN_BUY_IN: u32 = 20;
+const DEFAULT_TABLE_ID: u64 = 1;
+const DEFAULT_DENOM: &str = "uscrt";
+// 10% of every pot
+const MAX_RAKE_BASIS_POINTS: u16 = 1000;
+const MIN_SEATS: u8 = 2;
+const MAX_SEATS: u8 = 9;
+// about 10 minutes with 6 seconds blocks
+const DEFAULT_ACTION_TIMEOUT: u64 = 100;
+const MIN_SECRET_LENGTH: usize = 16;
+// bet, raise, re-raise and cap
+const FIXED_LIMIT_MAX_BETS: u8 = 4;
+// indexes of cards in the deck, for a hand dealt to N players:
+// first hole cards are 0..N and second hole cards are N..2N, in seat order,
+// the board is dealt after them, at 2N + the following offsets
+// Pre-flop betting round - burn offset 0
+const FLOP_FIRST_CARD: usize = 1;
+const FLOP_SECOND_CARD: usize = 2;
+const FLOP_THIRD_CARD: usize = 3;
+// Flop betting round - burn offset 4
+const TURN_CARD: usize = 5;
+// Turn betting round - burn offset 6
+const RIVER_CARD: usize = 7;
 // River betting round
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    Join { secret: u64 },
-    Raise { amount: u64 },
+    CreateTable {
+        big_blind: Uint128,
+        betting_structure: Option<BettingStructure>,
+        max_seats: Option<u8>,
+        action_timeout: Option<u64>,
+        min_buy_in: Option<u32>,
+        max_buy_in: Option<u32>,
+        currency: Option<Currency>,
+    },
+    // sits down at the table, the first hand starts once two players are seated
+    // and deals in everyone who joins before all its secrets are revealed
+    JoinTable {
+        table_id: u64,
+        commitment: Binary, // SHA-256 of the secret you'll reveal for the first hand
+    },
+    Join {
+        commitment: Binary,
+    }, // joins table 1, the room created with CreateRoom
+    Leave {},
+    // called by the SNIP-20 token of a table when tokens are sent to this contract
+    Receive {
+        sender: HumanAddr,
+        from: HumanAddr, // the player
+        amount: Uint128,
+        msg: Option<Binary>, // a Join, JoinTable or TopUp message
+    },
+    Reveal {
+        secret: Binary,
+    },
+    Raise {
+        amount: Uint128,
+    },
     Call {},
     Fold {},
     Check {},
-    Rematch {},
+    AllIn {},
+    Rematch {
+        commitment: Binary,
+    }, // SHA-256 of a fresh secret for the next hand
     Withdraw {},
     TopUp {},
+    // folds the player to act once they ran out of time, leaves out of the hand the players
+    // who didn't reveal in time, or between hands starts the next one without the players
+    // who didn't send Rematch in time
+    ClaimTimeout {},
+    CollectRake {}, // pays the rake of every table holding some to the collector
+    CreateViewingKey {
+        entropy: String,
+    },
+    SetViewingKey {
+        key: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleAnswer {
+    CreateViewingKey { key: String },
+    CreateTable { table_id: u64 },
+    // returned to a player by the transaction that dealt them in
+    Dealt { game_counter: u64, hand: Vec<Card> },
+}
+
+/// What happened at the table, in order. Every event is logged as an `event` attribute
+/// holding `{"table":<table id>,"hand":<game_counter>,"event":{...}}`, seats are player positions.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    Join {
+        seat: u8,
+        buy_in: Uint128,
+    },
+    TopUp {
+        seat: u8,
+        amount: Uint128,
+    },
+    Withdraw {
+        seat: u8,
+        amount: Uint128,
+    },
+    Leave {
+        seat: u8,
+        amount: Uint128,
+    },
+    Rematch {
+        seat: u8,
+    },
+    Reveal {
+        seat: u8,
+    },
+    MissedReveal {
+        seat: u8,
+    },
+    MissedRematch {
+        seat: u8,
+    },
+    HandCancelled {},
+    Deal {
+        button: u8,
+        seats: Vec<u8>,
+        deck_commitment: Binary,
+    },
+    PostBlind {
+        seat: u8,
+        amount: Uint128,
+    },
+    Check {
+        seat: u8,
+    },
+    Call {
+        seat: u8,
+        amount: Uint128,
+        all_in: bool,
+    },
+    Raise {
+        seat: u8,
+        amount: Uint128,
+        bet: Uint128,
+        all_in: bool,
+    },
+    Fold {
+        seat: u8,
+    },
+    TimedOut {
+        seat: u8,
+    },
+    ReturnUncalledBet {
+        seat: u8,
+        amount: Uint128,
+    },
+    Stage {
+        stage: Stage,
+        community_cards: Vec<Card>,
+    },
+    Show {
+        seat: u8,
+        hand: Vec<Card>,
+    },
+    WinPot {
+        pot: u8,
+        seat: u8,
+        amount: Uint128,
+    },
+}
+
+#[derive(Serialize)]
+struct LoggedEvent<'a> {
+    table: u64,
+    hand: u64,
+    event: &'a Event,
 }
 
 pub fn winner_winner_chicken_dinner(
+    currency: &Currency,
     contract_address: HumanAddr,
     player: HumanAddr,
     amount: Uint128,
-) -> HandleResponse {
-    HandleResponse {
-        messages: vec![CosmosMsg::Bank(BankMsg::Send {
+) -> StdResult<HandleResponse> {
+    let payout = match currency {
+        Currency::Native { denom } => CosmosMsg::Bank(BankMsg::Send {
             from_address: contract_address,
             to_address: player,
             amount: vec![Coin {
-                denom: "uscrt".to_string(),
+                denom: denom.clone(),
                 amount,
             }],
-        })],
+        }),
+        Currency::Snip20 { address, code_hash } => {
+            transfer_msg(address, code_hash, player, amount)?
+        }
+    };
+
+    Ok(HandleResponse {
+        messages: vec![payout],
         log: vec![],
         data: None,
-    }
+    })
 }
 
-fn can_deposit(env: &Env, table: &Table, current_amount: u64) -> StdResult<i64> {
-    let deposit: Uint128;
+/// The coins of the table's denom sent along with a deposit, and the other coins that have to
+/// go back to the sender. Tables that play with a token only take deposits through `Receive`.
+fn sent_funds(env: &Env, table: &Table) -> StdResult<(Uint128, Vec<Coin>)> {
+    let denom = match &table.currency {
+        Currency::Native { denom } => denom,
+        Currency::Snip20 { address, .. } => {
+            return Err(ContractError::WrongDenom {
+                expected: address.to_string(),
+            }
+            .into())
+        }
+    };
+
+    if env.message.sent_funds.is_empty() {
+        return Err(ContractError::NoFunds.into());
+    }
+
+    let mut deposit = Uint128::zero();
+    let mut refund = vec![];
+    for coin in env.message.sent_funds.iter() {
+        if coin.denom == *denom {
+            deposit = add_chips(deposit, coin.amount)?;
+        } else {
+            refund.push(coin.clone());
+        }
+    }
 
-    if env.message.sent_funds.len() == 0 {
-        return Err(StdError::generic_err("SHOW ME THE MONEY"));
-    } else {
-        if env.message.sent_funds[0].denom != "uscrt" {
-            return Err(StdError::generic_err("WRONG MONEY"));
+    if refund.len() == env.message.sent_funds.len() {
+        return Err(ContractError::WrongDenom {
+            expected: denom.clone(),
         }
-        deposit = env.message.sent_funds[0].amount;
+        .into());
+    }
+    Ok((deposit, refund))
+}
+
+/// Sends coins that weren't deposited back to the sender.
+fn refund_msg(env: &Env, coins: Vec<Coin>) -> Option<CosmosMsg> {
+    let coins: Vec<Coin> = coins
+        .into_iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+    if coins.is_empty() {
+        return None;
+    }
 
-        if deposit.u128() as u64 + current_amount < table.min_credit {
-            return Err(StdError::generic_err("GTFO DIRTY SHORT STACKER"));
+    Some(CosmosMsg::Bank(BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: env.message.sender.clone(),
+        amount: coins,
+    }))
+}
+
+/// Checks that depositing `deposit` on top of `current_amount` keeps the stack within the buy-in range.
+fn can_deposit(table: &Table, current_amount: Uint128, deposit: Uint128) -> StdResult<()> {
+    let stack = add_chips(current_amount, deposit)?;
+    if stack < table.min_credit {
+        return Err(ContractError::BelowMinBuyIn {
+            min: table.min_credit.u128(),
         }
+        .into());
+    }
 
-        if deposit.u128() as u64 + current_amount > table.max_credit {
-            return Err(StdError::generic_err("GTFO DIRTY DEEP STACKER"));
+    if stack > table.max_credit {
+        return Err(ContractError::AboveMaxBuyIn {
+            max: table.max_credit.u128(),
         }
+        .into());
+    }
+    Ok(())
+}
+
+/// Chip arithmetic fails the transaction instead of wrapping around or aborting the contract.
+fn add_chips(a: Uint128, b: Uint128) -> StdResult<Uint128> {
+    match a.u128().checked_add(b.u128()) {
+        Some(sum) => Ok(Uint128(sum)),
+        None => Err(ContractError::Overflow.into()),
+    }
+}
+
+fn sub_chips(a: Uint128, b: Uint128) -> StdResult<Uint128> {
+    match a.u128().checked_sub(b.u128()) {
+        Some(difference) => Ok(Uint128(difference)),
+        None => Err(ContractError::Overflow.into()),
+    }
+}
+
+fn mul_chips(a: Uint128, times: u128) -> StdResult<Uint128> {
+    match a.u128().checked_mul(times) {
+        Some(product) => Ok(Uint128(product)),
+        None => Err(ContractError::Overflow.into()),
     }
-    Ok(deposit.u128() as i64)
+}
+
+/// Stores the player's commitment to the secret they'll reveal for the next hand.
+/// The secret revealed for the previous hand is forgotten.
+fn store_commitment<S: Storage>(
+    storage: &mut S,
+    player: &HumanAddr,
+    commitment: &Binary,
+) -> StdResult<()> {
+    if commitment.as_slice().len() != 32 {
+        return Err(ContractError::InvalidCommitment.into());
+    }
+
+    if load_commitment(storage, player).as_deref() == Some(commitment.as_slice()) {
+        return Err(ContractError::ReusedCommitment.into());
+    }
+
+    save_commitment(storage, player, commitment.as_slice());
+    remove_secret(storage, player);
+
+    Ok(())
 }
 
 pub fn handle<S: Storage, A: Api, Q: Querier>(
@@ -224,493 +707,1606 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: HandleMsg,
 ) -> HandleResult {
-    return match msg {
-        HandleMsg::TopUp {} => {
-            let me = Some(env.message.sender.clone());
-
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-
-            if me == table.player_b {
-                let deposit = can_deposit(&env, &table, table.player_b_wallet as u64)?;
-                table.player_b_wallet += deposit;
-            } else if me == table.player_a {
-                let deposit = can_deposit(&env, &table, table.player_a_wallet as u64)?;
-                table.player_a_wallet += deposit;
-            } else {
-                return Err(StdError::generic_err(
-                    "You are not a player, or you are broke! Either way, go away!",
-                ));
-            }
+    // deposits account for the coins sent with them, coins sent with anything else go back
+    let refund = match msg {
+        HandleMsg::Join { .. } | HandleMsg::JoinTable { .. } | HandleMsg::TopUp {} => None,
+        _ => refund_msg(&env, env.message.sent_funds.clone()),
+    };
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+    let mut response = try_handle(deps, env, msg)?;
+    response.messages.extend(refund);
+    Ok(response)
+}
 
-            Ok(HandleResponse::default())
+fn try_handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::TopUp {} => {
+            let table = player_table(&deps.storage, &env.message.sender)?;
+            let (deposit, refund) = sent_funds(&env, &table)?;
+            let mut response = top_up(&mut deps.storage, table, &env.message.sender, deposit)?;
+            response.messages.extend(refund_msg(&env, refund));
+            Ok(response)
         }
         HandleMsg::Withdraw {} => {
-            let player_name = Some(env.message.sender.clone());
-
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-
-            if player_name == table.player_b && table.player_b_wallet != 0 {
-                //fold player b
-                if !table.stage.no_more_action() {
-                    table.stage = Stage::EndedWinnerA;
-                    table.player_a_wallet += (table.player_a_bet + table.player_b_bet) as i64;
-                    table.player_a_bet = 0;
-                    table.player_b_bet = 0;
-                    table.player_a_win_counter += 1;
-                    table.last_play = Some(String::from("Player B folded"));
-                }
-                let amount = table.player_b_wallet;
-                table.player_b_wallet = 0;
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
 
-                deps.storage
-                    .set(b"table", &serde_json::to_vec(&table).unwrap());
+            let seat = match table.seat_of(&env.message.sender) {
+                Some(seat) if !table.players[seat].wallet.is_zero() => seat,
+                _ => return Err(ContractError::NothingToWithdraw.into()),
+            };
 
-                return Ok(winner_winner_chicken_dinner(
-                    env.contract.address,
-                    player_name.unwrap(),
-                    Uint128(amount as u128),
-                ));
-            } else if player_name == table.player_a && table.player_a_wallet != 0 {
-                //fold player a
-                if !table.stage.no_more_action() {
-                    table.stage = Stage::EndedWinnerB;
-                    table.player_b_wallet += (table.player_a_bet + table.player_b_bet) as i64;
-                    table.player_a_bet = 0;
-                    table.player_b_bet = 0;
-                    table.player_b_win_counter += 1;
-                    table.last_play = Some(String::from("Player A folded"));
+            if table.stage == Stage::WaitingForSecrets && table.players[seat].in_hand {
+                // nothing was bet yet, just deal the hand without the player
+                table.players[seat].in_hand = false;
+                table.emit(Event::MissedReveal {
+                    seat: table.position(seat),
+                });
+                table.settle_reveals(deps, &env)?;
+                table.record_action(&env);
+            } else if !table.stage.no_more_action() && table.players[seat].is_contending() {
+                // fold the player before cashing out
+                table.players[seat].folded = true;
+                table.last_play = Some(format!("Seat {} folded", table.players[seat].position + 1));
+                table.emit(Event::Fold {
+                    seat: table.position(seat),
+                });
+
+                if table.turn == Some(env.message.sender.clone()) {
+                    table.next_turn(deps, seat)?;
+                    table.record_action(&env);
+                } else if table.contenders() == 1 {
+                    table.win_uncontested(&mut deps.storage)?;
                 }
-                let amount = table.player_a_wallet;
-                table.player_a_wallet = 0;
+            }
+
+            let amount = table.players[seat].wallet;
+            table.players[seat].wallet = Uint128::zero();
+            table.emit(Event::Withdraw {
+                seat: table.position(seat),
+                amount,
+            });
 
-                deps.storage
-                    .set(b"table", &serde_json::to_vec(&table).unwrap());
+            save_table(&mut deps.storage, &table)?;
 
-                return Ok(winner_winner_chicken_dinner(
-                    env.contract.address,
-                    player_name.unwrap(),
-                    Uint128(amount as u128),
-                ));
+            let mut response = winner_winner_chicken_dinner(
+                &table.currency,
+                env.contract.address,
+                env.message.sender,
+                amount,
+            )?;
+            response.log = table.log;
+            Ok(response)
+        }
+        HandleMsg::CreateTable {
+            big_blind,
+            betting_structure,
+            max_seats,
+            action_timeout,
+            min_buy_in,
+            max_buy_in,
+            currency,
+        } => {
+            let mut lobby = load_lobby(&deps.storage)?;
+            if !lobby.open {
+                return Err(ContractError::NotALobby.into());
             }
 
-            Err(StdError::generic_err(
-                "You are not a player, or you are broke! Either way, go away!",
-            ))
+            lobby.table_count += 1;
+            let settings = TableSettings {
+                big_blind,
+                betting_structure,
+                max_seats,
+                action_timeout,
+                min_buy_in,
+                max_buy_in,
+                currency,
+                rake: lobby.rake.clone(),
+            };
+            let table = new_table(lobby.table_count, &env, settings)?;
+
+            save_table(&mut deps.storage, &table)?;
+            save_lobby(&mut deps.storage, &lobby)?;
+
+            Ok(HandleResponse {
+                messages: table.register_receive(&env)?,
+                log: vec![log("table_id", table.id)],
+                data: Some(to_binary(&HandleAnswer::CreateTable {
+                    table_id: table.id,
+                })?),
+            })
         }
-        HandleMsg::Join { secret } => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
+        HandleMsg::CollectRake {} => {
+            let lobby = load_lobby(&deps.storage)?;
+            match &lobby.rake {
+                Some(rake) if rake.collector == env.message.sender => {}
+                _ => return Err(ContractError::NotRakeCollector.into()),
+            }
 
-            let deposit = can_deposit(&env, &table, 0)?;
+            // one payout per table, tables can play with different currencies
+            let mut messages = vec![];
+            let mut logs = vec![];
+            for table_id in load_raked_tables(&deps.storage)? {
+                let mut table = load_table(&deps.storage, table_id)?;
+                if table.uncollected_rake.is_zero() {
+                    continue;
+                }
 
-            if table.player_a.is_some() && table.player_b.is_some() {
-                return Err(StdError::generic_err("Table is full."));
+                let amount = table.uncollected_rake;
+                table.uncollected_rake = Uint128::zero();
+                save_table(&mut deps.storage, &table)?;
+
+                let payout = winner_winner_chicken_dinner(
+                    &table.currency,
+                    env.contract.address.clone(),
+                    env.message.sender.clone(),
+                    amount,
+                )?;
+                messages.extend(payout.messages);
+                logs.push(log("rake", format!("{}:{}", table.id, amount)));
             }
 
-            let player_secret = &secret.to_be_bytes();
-
-            if table.player_a.is_none() {
-                // player a - just store
-                deps.storage.set(b"player_a_secret", player_secret);
+            if messages.is_empty() {
+                return Err(ContractError::NothingToCollect.into());
+            }
+            save_raked_tables(&mut deps.storage, &[])?;
+            Ok(HandleResponse {
+                messages,
+                log: logs,
+                data: None,
+            })
+        }
+        HandleMsg::Join { commitment } => try_handle(
+            deps,
+            env,
+            HandleMsg::JoinTable {
+                table_id: DEFAULT_TABLE_ID,
+                commitment,
+            },
+        ),
+        HandleMsg::JoinTable {
+            table_id,
+            commitment,
+        } => {
+            let table = load_table(&deps.storage, table_id)?;
+            let (deposit, refund) = sent_funds(&env, &table)?;
+            let mut response = join_table(
+                &mut deps.storage,
+                &env,
+                table,
+                &env.message.sender,
+                &commitment,
+                deposit,
+            )?;
+            response.messages.extend(refund_msg(&env, refund));
+            Ok(response)
+        }
+        HandleMsg::Receive {
+            from, amount, msg, ..
+        } => {
+            let msg: HandleMsg = match msg {
+                Some(msg) => from_binary(&msg)?,
+                None => return Err(ContractError::InvalidReceiveMsg.into()),
+            };
+            let (table, commitment) = match msg {
+                HandleMsg::Join { commitment } => (
+                    load_table(&deps.storage, DEFAULT_TABLE_ID)?,
+                    Some(commitment),
+                ),
+                HandleMsg::JoinTable {
+                    table_id,
+                    commitment,
+                } => (load_table(&deps.storage, table_id)?, Some(commitment)),
+                HandleMsg::TopUp {} => (player_table(&deps.storage, &from)?, None),
+                _ => return Err(ContractError::InvalidReceiveMsg.into()),
+            };
 
-                table.player_a = Some(env.message.sender.clone());
-                table.player_a_wallet = deposit;
-                table.starter = Some(env.message.sender.clone());
-                table.turn = Some(env.message.sender.clone());
-                deps.storage
-                    .set(b"table", &serde_json::to_vec(&table).unwrap());
+            // only the token the table plays with can deposit, on behalf of the owner of the tokens
+            match &table.currency {
+                Currency::Snip20 { address, .. } if *address == env.message.sender => {}
+                currency => {
+                    return Err(ContractError::WrongDenom {
+                        expected: currency.name(),
+                    }
+                    .into())
+                }
+            }
 
-                return Ok(HandleResponse::default());
+            match commitment {
+                Some(commitment) => {
+                    join_table(&mut deps.storage, &env, table, &from, &commitment, amount)
+                }
+                None => top_up(&mut deps.storage, table, &from, amount),
             }
+        }
+        HandleMsg::Leave {} => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            let seat = table.seat_of(&env.message.sender).unwrap();
 
-            // player b - we can now shuffle the deck
-            deps.storage.set(b"player_b_secret", player_secret);
+            // chips the player put in the pot stay there until the hand is over
+            if !table.stage.no_more_action() && table.players[seat].in_hand {
+                return Err(ContractError::HandInProgress.into());
+            }
 
-            let player_a_secret = deps.storage.get(b"player_a_secret").unwrap();
+            let player = table.players.remove(seat);
+            table.emit(Event::Leave {
+                seat: player.position,
+                amount: player.wallet,
+            });
+            unseat_player(&mut deps.storage, &env.message.sender);
 
-            let mut combined_secret = player_a_secret.clone();
-            combined_secret.extend(player_secret);
-            combined_secret.extend(&(0 as u64).to_be_bytes()); // game counter
-            let seed: [u8; 32] = Sha256::digest(&combined_secret).into();
+            save_table(&mut deps.storage, &table)?;
 
-            let mut rng = ChaChaRng::from_seed(seed);
-            let mut deck: Vec<Card> = Deck::default().into_iter().collect();
-            deck.shuffle(&mut rng);
+            let mut response = if player.wallet.is_zero() {
+                HandleResponse::default()
+            } else {
+                winner_winner_chicken_dinner(
+                    &table.currency,
+                    env.contract.address,
+                    env.message.sender,
+                    player.wallet,
+                )?
+            };
+            response.log = table.log;
+            Ok(response)
+        }
+        HandleMsg::Raise { amount } => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            let seat = table.acting_seat(&env.message.sender)?;
+
+            if !table
+                .players
+                .iter()
+                .enumerate()
+                .any(|(i, p)| i != seat && p.can_act())
+            {
+                return Err(ContractError::EveryoneAllIn.into());
+            }
 
-            deps.storage
-                .set(b"deck", &serde_json::to_vec(&deck).unwrap());
+            // only short all-ins were raised since this player acted, they can call or fold
+            if table.players[seat].acted {
+                return Err(ContractError::BettingNotReopened.into());
+            }
 
-            table.player_b = Some(env.message.sender.clone());
-            table.player_b_wallet = deposit;
+            let max_bet = table.max_bet();
+            let to_call = sub_chips(max_bet, table.players[seat].bet)?;
+            let cost = add_chips(to_call, amount)?;
+            let all_in = table.players[seat].wallet == cost;
+            table.validate_raise(amount, to_call, all_in)?;
 
-            table.stage = table.stage.next_round();
-            table.starter = table.player_a.clone();
-            table.turn = table.player_a.clone();
+            if table.players[seat].wallet < cost {
+                return Err(ContractError::InsufficientFunds.into());
+            }
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+            let full_raise = amount >= table.full_raise()?;
+            if amount >= table.last_raise {
+                table.last_raise = amount;
+            }
+            table.street_bets = table.street_bets.saturating_add(1);
+
+            let bet = add_chips(max_bet, amount)?;
+            let player = &mut table.players[seat];
+            player.wallet = sub_chips(player.wallet, cost)?;
+            player.bet = bet;
+            player.committed = add_chips(player.committed, cost)?;
+
+            // a full raise re-opens the action for everyone else
+            if full_raise {
+                for player in table.players.iter_mut() {
+                    player.acted = false;
+                }
+            }
+            table.players[seat].acted = true;
 
-            Ok(HandleResponse::default())
+            table.last_play = Some(format!(
+                "Seat {} raised by {} credits",
+                table.players[seat].position + 1,
+                amount
+            ));
+            table.emit(Event::Raise {
+                seat: table.position(seat),
+                amount,
+                bet,
+                all_in,
+            });
+            table.next_turn(deps, seat)?;
+            table.record_action(&env);
+
+            save_table(&mut deps.storage, &table)?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
         }
-        HandleMsg::Raise { amount } => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-            if table.stage.no_more_action() {
-                return Err(StdError::generic_err("Action hasn't started yet"));
-            }
+        HandleMsg::AllIn {} => {
+            let table = player_table(&deps.storage, &env.message.sender)?;
 
-            let me = Some(env.message.sender.clone());
+            let (my_wallet, my_bet) = match table.seat_of(&env.message.sender) {
+                Some(seat) => (table.players[seat].wallet, table.players[seat].bet),
+                None => (Uint128::zero(), Uint128::zero()),
+            };
 
-            if me != table.player_a && me != table.player_b {
-                return Err(StdError::generic_err("You are not a player, go away!"));
+            // shoving with no more than what it costs to call is a call for less,
+            // anything above that is a raise by the rest of the wallet, capped at
+            // the largest raise the betting structure allows
+            let to_call = sub_chips(table.max_bet(), my_bet)?;
+            if my_wallet <= to_call {
+                return try_handle(deps, env, HandleMsg::Call {});
             }
 
-            if me != table.turn {
-                return Err(StdError::generic_err("It's not your turn."));
+            let mut amount = sub_chips(my_wallet, to_call)?;
+            if let Some(max_raise) = table.max_raise(to_call)? {
+                amount = std::cmp::min(amount, max_raise);
             }
 
-            if me == table.player_a {
-                if table.player_a_wallet < amount as i64 {
-                    return Err(StdError::generic_err(
-                        "You cannot raise more than you have!",
-                    ));
-                }
+            if amount.is_zero() {
+                try_handle(deps, env, HandleMsg::Call {})
+            } else {
+                try_handle(deps, env, HandleMsg::Raise { amount })
+            }
+        }
+        HandleMsg::Call {} => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            let seat = table.acting_seat(&env.message.sender)?;
 
-                // I'm player A
-                table.player_a_wallet -= (table.player_b_bet + amount - table.player_a_bet) as i64;
-                if table.player_a_wallet < 0 {
-                    return Err(StdError::generic_err(
-                        "You don't have enough credits to raise by that much.",
-                    ));
-                }
-                table.player_a_bet = table.player_b_bet + amount;
+            let to_call = sub_chips(table.max_bet(), table.players[seat].bet)?;
+            if to_call.is_zero() {
+                return Err(ContractError::NothingToCall.into());
+            }
 
-                table.last_play = Some(String::from(format!(
-                    "Player A raised by {} credits",
-                    amount
-                )));
-                table.turn = table.player_b.clone();
-            } else {
-                // I'm player B
-                if table.player_b_wallet < amount as i64 {
-                    return Err(StdError::generic_err(
-                        "You cannot raise more than you have!",
-                    ));
+            let position = table.players[seat].position + 1;
+            let player = &mut table.players[seat];
+            let event = if player.wallet <= to_call {
+                // short stack calls all-in for less, the uncalled part goes back to the bettor
+                // when the betting round ends
+                let amount = player.wallet;
+                player.bet = add_chips(player.bet, amount)?;
+                player.committed = add_chips(player.committed, amount)?;
+                player.wallet = Uint128::zero();
+
+                table.last_play = Some(format!("Seat {} called all-in", position));
+                Event::Call {
+                    seat: position - 1,
+                    amount,
+                    all_in: true,
                 }
-
-                table.player_b_wallet -= (table.player_a_bet + amount - table.player_b_bet) as i64;
-                if table.player_b_wallet < 0 {
-                    return Err(StdError::generic_err(
-                        "You don't have enough credits to raise by that much.",
-                    ));
+            } else {
+                player.wallet = sub_chips(player.wallet, to_call)?;
+                player.bet = add_chips(player.bet, to_call)?;
+                player.committed = add_chips(player.committed, to_call)?;
+
+                table.last_play = Some(format!("Seat {} called", position));
+                Event::Call {
+                    seat: position - 1,
+                    amount: to_call,
+                    all_in: false,
                 }
-                table.player_b_bet = table.player_a_bet + amount;
+            };
+            table.players[seat].acted = true;
+            table.emit(event);
 
-                table.last_play = Some(String::from(format!(
-                    "Player B raised by {} credits",
-                    amount
-                )));
-                table.turn = table.player_a.clone();
-            }
+            table.next_turn(deps, seat)?;
+            table.record_action(&env);
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+            save_table(&mut deps.storage, &table)?;
 
-            Ok(HandleResponse::default())
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
         }
-        HandleMsg::Call {} => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-            if table.stage.no_more_action() {
-                return Err(StdError::generic_err("Action hasn't started yet"));
-            }
-
-            let me = Some(env.message.sender.clone());
+        HandleMsg::Fold {} => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            let seat = table.acting_seat(&env.message.sender)?;
 
-            if me != table.player_a && me != table.player_b {
-                return Err(StdError::generic_err("You are not a player, go away!"));
-            }
+            table.players[seat].folded = true;
+            table.last_play = Some(format!("Seat {} folded", table.players[seat].position + 1));
+            table.emit(Event::Fold {
+                seat: table.position(seat),
+            });
 
-            if me != table.turn {
-                return Err(StdError::generic_err("It's not your turn."));
-            }
+            table.next_turn(deps, seat)?;
+            table.record_action(&env);
 
-            if me == table.player_a {
-                // I'm player A
-                table.player_a_wallet -= (table.player_b_bet - table.player_a_bet) as i64;
-                if table.player_a_wallet < 0 {
-                    return Err(StdError::generic_err(
-                        "You cannot Call, your bet is bigger or equals to the other player's bet.",
-                    ));
-                }
-                table.player_a_bet = table.player_b_bet;
+            save_table(&mut deps.storage, &table)?;
 
-                table.last_play = Some(String::from("Player A called"));
-            } else {
-                // I'm player B
-                table.player_b_wallet -= (table.player_a_bet - table.player_b_bet) as i64;
-                if table.player_b_wallet < 0 {
-                    return Err(StdError::generic_err(
-                        "You cannot Call, your bet is bigger or equals to the other player's bet.",
-                    ));
-                }
-                table.player_b_bet = table.player_a_bet;
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
+        }
+        HandleMsg::Check {} => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            let seat = table.acting_seat(&env.message.sender)?;
 
-                table.last_play = Some(String::from("Player B called"));
+            if table.players[seat].bet != table.max_bet() {
+                return Err(ContractError::CannotCheck.into());
             }
 
-            table.turn = table.player_a.clone();
-            table.goto_next_stage(deps);
+            table.players[seat].acted = true;
+            table.last_play = Some(format!("Seat {} checked", table.players[seat].position + 1));
+            table.emit(Event::Check {
+                seat: table.position(seat),
+            });
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+            table.next_turn(deps, seat)?;
+            table.record_action(&env);
 
-            Ok(HandleResponse::default())
+            save_table(&mut deps.storage, &table)?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
         }
-        HandleMsg::Fold {} => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-            if table.stage.no_more_action() {
-                return Err(StdError::generic_err("Action hasn't started yet"));
+        HandleMsg::Rematch { commitment } => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+
+            if !table.stage.no_more_action() {
+                return Err(ContractError::HandInProgress.into());
             }
 
-            let me = Some(env.message.sender.clone());
+            let seat = match table.seat_of(&env.message.sender) {
+                Some(seat) => seat,
+                None => return Err(ContractError::NotAPlayer.into()),
+            };
 
-            if me != table.player_a && me != table.player_b {
-                return Err(StdError::generic_err("You are not a player, go away!"));
+            if table.players[seat].wallet < table.big_blind {
+                return Err(ContractError::CannotCoverBlinds.into());
             }
 
-            if me != table.turn {
-                return Err(StdError::generic_err("It's not your turn."));
-            }
+            store_commitment(&mut deps.storage, &env.message.sender, &commitment)?;
+            table.players[seat].wants_rematch = true;
+            table.players[seat].sitting_out = false;
+            table.emit(Event::Rematch {
+                seat: table.position(seat),
+            });
 
-            if me == table.player_a {
-                table.stage = Stage::EndedWinnerB;
-                table.player_b_wallet += (table.player_a_bet + table.player_b_bet) as i64;
-                table.player_b_win_counter += 1;
-                table.last_play = Some(String::from("Player A folded"));
-            } else {
-                table.stage = Stage::EndedWinnerA;
-                table.player_a_wallet += (table.player_a_bet + table.player_b_bet) as i64;
-                table.player_a_win_counter += 1;
-                table.last_play = Some(String::from("Player B folded"));
+            // players who can't cover the big blind or timed out sit out,
+            // everyone else has to approve
+            if table.players.iter().any(|p| table.blocks_next_hand(p)) {
+                // not everyone approved a rematch yet
+                save_table(&mut deps.storage, &table)?;
+                return Ok(HandleResponse {
+                    messages: vec![],
+                    log: table.log,
+                    data: None,
+                });
             }
 
-            table.player_a_bet = 0;
-            table.player_b_bet = 0;
+            table.begin_hand()?;
+            table.record_action(&env);
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+            save_table(&mut deps.storage, &table)?;
 
-            Ok(HandleResponse::default())
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
         }
-        HandleMsg::Check {} => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
-            if table.stage.no_more_action() {
-                return Err(StdError::generic_err("Action hasn't started yet"));
+        HandleMsg::Reveal { secret } => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+
+            if table.stage != Stage::WaitingForSecrets {
+                return Err(ContractError::NothingToReveal.into());
             }
 
-            let me = Some(env.message.sender.clone());
+            let seat = match table.seat_of(&env.message.sender) {
+                Some(seat) if table.players[seat].in_hand => seat,
+                _ => return Err(ContractError::NotAPlayer.into()),
+            };
 
-            if me != table.player_a && me != table.player_b {
-                return Err(StdError::generic_err("You are not a player, go away!"));
+            if table.players[seat].revealed {
+                return Err(ContractError::AlreadyRevealed.into());
             }
 
-            if me != table.turn {
-                return Err(StdError::generic_err("It's not your turn."));
+            if secret.as_slice().len() < MIN_SECRET_LENGTH {
+                return Err(ContractError::SecretTooShort {
+                    min: MIN_SECRET_LENGTH,
+                }
+                .into());
             }
 
-            if table.player_a_bet != table.player_b_bet {
-                return Err(StdError::generic_err(
-                    "You cannot check, must Call, Raise or Fold.",
-                ));
+            let commitment = load_commitment(&deps.storage, &env.message.sender);
+            let hash = Sha256::digest(secret.as_slice());
+            if commitment.as_deref() != Some(hash.as_slice()) {
+                return Err(ContractError::SecretMismatch.into());
             }
 
-            if me == table.player_a {
-                table.last_play = Some(String::from("Player A checked"));
-                table.turn = table.player_b.clone();
-            } else {
-                table.last_play = Some(String::from("Player B checked"));
-                table.turn = table.player_a.clone();
-            }
+            save_secret(&mut deps.storage, &env.message.sender, secret.as_slice());
+            table.players[seat].revealed = true;
+            table.last_play = Some(format!(
+                "Seat {} revealed their secret",
+                table.players[seat].position + 1
+            ));
+            table.emit(Event::Reveal {
+                seat: table.position(seat),
+            });
 
-            if table.turn == table.starter {
-                table.goto_next_stage(deps);
-            }
+            let dealt_before = table.game_counter;
+            table.settle_reveals(deps, &env)?;
+            table.record_action(&env);
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+            save_table(&mut deps.storage, &table)?;
+
+            Ok(HandleResponse {
+                messages: vec![],
+                data: table.deal_answer(&deps.storage, dealt_before, &env.message.sender)?,
+                log: table.log,
+            })
+        }
+        HandleMsg::CreateViewingKey { entropy } => {
+            let key = create_viewing_key(&mut deps.storage, &env, &entropy);
+            set_viewing_key(&mut deps.storage, &env.message.sender, &key);
+
+            Ok(HandleResponse {
+                messages: vec![],
+                log: vec![],
+                data: Some(to_binary(&HandleAnswer::CreateViewingKey { key })?),
+            })
+        }
+        HandleMsg::SetViewingKey { key } => {
+            set_viewing_key(&mut deps.storage, &env.message.sender, &key);
 
             Ok(HandleResponse::default())
         }
-        HandleMsg::Rematch {} => {
-            let mut table: Table =
-                serde_json::from_slice(&deps.storage.get(b"table").unwrap()).unwrap();
+        HandleMsg::ClaimTimeout {} => {
+            let mut table = player_table(&deps.storage, &env.message.sender)?;
+            if table.stage.no_more_action() {
+                if !table.players.iter().any(|p| table.blocks_next_hand(p)) {
+                    return Err(ContractError::HandNotStarted.into());
+                }
+                match table.seat_of(&env.message.sender) {
+                    Some(seat) if table.players[seat].wants_rematch => {}
+                    _ => return Err(ContractError::RematchBeforeClaiming.into()),
+                }
+                table.check_timeout(&env)?;
+
+                // the next hand is dealt without the players that didn't approve it
+                for seat in 0..table.players.len() {
+                    if table.blocks_next_hand(&table.players[seat]) {
+                        table.players[seat].sitting_out = true;
+                        table.emit(Event::MissedRematch {
+                            seat: table.position(seat),
+                        });
+                    }
+                }
+                table.last_play = Some(String::from(
+                    "Players that didn't approve the next hand in time are sitting out",
+                ));
+                if table.ready_players() >= 2 {
+                    table.begin_hand()?;
+                }
+                table.record_action(&env);
 
-            if !table.stage.no_more_action() {
-                return Err(StdError::generic_err("You can't start a new game now!"));
+                save_table(&mut deps.storage, &table)?;
+
+                return Ok(HandleResponse {
+                    messages: vec![],
+                    log: table.log,
+                    data: None,
+                });
             }
 
-            let me = Some(env.message.sender.clone());
+            let seat = match table.seat_of(&env.message.sender) {
+                Some(seat) if table.players[seat].is_contending() => seat,
+                _ => return Err(ContractError::NotAPlayer.into()),
+            };
+
+            if table.stage == Stage::WaitingForSecrets && !table.players[seat].revealed {
+                return Err(ContractError::RevealBeforeClaiming.into());
+            }
 
-            if me != table.player_a && me != table.player_b {
-                return Err(StdError::generic_err("You are not a player, go away!"));
+            if Some(env.message.sender.clone()) == table.turn {
+                return Err(ContractError::CannotTimeOutYourself.into());
             }
 
-            if table.player_b_wallet == 0 || table.player_a_wallet == 0 {
-                return Err(StdError::generic_err(
-                    "One of the players must deposit to continue playing",
+            table.check_timeout(&env)?;
+
+            if table.stage == Stage::WaitingForSecrets {
+                // the hand is dealt without the players that didn't reveal
+                for seat in 0..table.players.len() {
+                    if table.players[seat].in_hand && !table.players[seat].revealed {
+                        table.players[seat].in_hand = false;
+                        table.emit(Event::MissedReveal {
+                            seat: table.position(seat),
+                        });
+                    }
+                }
+                table.last_play = Some(String::from(
+                    "Players that didn't reveal their secret in time were left out of the hand",
                 ));
+                let dealt_before = table.game_counter;
+                table.settle_reveals(deps, &env)?;
+                table.record_action(&env);
+
+                save_table(&mut deps.storage, &table)?;
+
+                return Ok(HandleResponse {
+                    messages: vec![],
+                    data: table.deal_answer(&deps.storage, dealt_before, &env.message.sender)?,
+                    log: table.log,
+                });
             }
 
-            if me == table.player_a {
-                table.player_a_wants_rematch = true;
+            // the absent player checks if they can, otherwise they fold
+            let absent = table.seat_of(table.turn.as_ref().unwrap()).unwrap();
+            let position = table.players[absent].position + 1;
+            table.emit(Event::TimedOut { seat: position - 1 });
+            if table.players[absent].bet == table.max_bet() {
+                table.players[absent].acted = true;
+                table.last_play = Some(format!("Seat {} timed out and checked", position));
+                table.emit(Event::Check { seat: position - 1 });
             } else {
-                table.player_b_wants_rematch = true;
+                table.players[absent].folded = true;
+                table.last_play = Some(format!("Seat {} timed out and folded", position));
+                table.emit(Event::Fold { seat: position - 1 });
             }
 
-            if !table.player_b_wants_rematch || !table.player_a_wants_rematch {
-                // not everyone approved a rematch yet
-                deps.storage
-                    .set(b"table", &serde_json::to_vec(&table).unwrap());
-                return Ok(HandleResponse::default());
-            }
+            table.next_turn(deps, absent)?;
+            table.record_action(&env);
+
+            save_table(&mut deps.storage, &table)?;
 
-            table.game_counter += 1;
+            Ok(HandleResponse {
+                messages: vec![],
+                log: table.log,
+                data: None,
+            })
+        }
+    }
+}
 
-            let player_a_secret = deps.storage.get(b"player_a_secret").unwrap();
-            let player_b_secret = deps.storage.get(b"player_b_secret").unwrap();
+/// Seats `player` at `table` with a `deposit` that was already received.
+fn join_table<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    mut table: Table,
+    player: &HumanAddr,
+    commitment: &Binary,
+    deposit: Uint128,
+) -> HandleResult {
+    can_deposit(&table, Uint128::zero(), deposit)?;
 
-            let mut combined_secret = player_a_secret.clone();
-            combined_secret.extend(player_b_secret);
-            combined_secret.extend(&table.game_counter.to_be_bytes()); // game counter
-            let seed: [u8; 32] = Sha256::digest(&combined_secret).into();
+    if player_table_id(storage, player)?.is_some() {
+        return Err(ContractError::AlreadySeated.into());
+    }
 
-            let mut rng = ChaChaRng::from_seed(seed);
-            let mut deck: Vec<Card> = Deck::default().into_iter().collect();
-            deck.shuffle(&mut rng);
+    if table.players.len() >= table.max_seats as usize {
+        return Err(ContractError::TableFull.into());
+    }
 
-            deps.storage
-                .set(b"deck", &serde_json::to_vec(&deck).unwrap());
+    store_commitment(storage, player, commitment)?;
+
+    // take the first free position, seats left by other players are reused
+    let position = (0..table.max_seats)
+        .find(|&position| table.players.iter().all(|p| p.position != position))
+        .unwrap();
+    let seat = table
+        .players
+        .iter()
+        .position(|p| p.position > position)
+        .unwrap_or(table.players.len());
+    table.players.insert(
+        seat,
+        Player {
+            address: player.clone(),
+            position,
+            wallet: deposit,
+            bet: Uint128::zero(),
+            committed: Uint128::zero(),
+            hand: vec![],
+            in_hand: false,
+            deal_index: None,
+            revealed: false,
+            folded: false,
+            acted: false,
+            wants_rematch: true,
+            sitting_out: false,
+            win_counter: 0,
+        },
+    );
+    table.emit(Event::Join {
+        seat: position,
+        buy_in: deposit,
+    });
+
+    // the first hand starts as soon as two players are seated, players joining while
+    // its secrets are being revealed are dealt in too, later ones on the next rematch
+    if table.stage == Stage::WaitingForSecrets
+        && table.game_counter == 0
+        && table.players[seat].wallet >= table.big_blind
+    {
+        table.players[seat].in_hand = true;
+        table.players[seat].wants_rematch = false;
+    } else if table.stage == Stage::WaitingForPlayersToJoin && table.ready_players() >= 2 {
+        table.begin_hand()?;
+        table.record_action(env);
+    }
 
-            table.stage = Stage::PreFlop;
-            table.turn = table.starter.clone();
-            table.last_play = None;
+    seat_player(storage, player, table.id)?;
+    save_table(storage, &table)?;
 
-            table.community_cards = vec![];
+    Ok(HandleResponse {
+        messages: vec![],
+        log: table.log,
+        data: None,
+    })
+}
 
-            table.player_a_bet = 0;
-            table.player_b_bet = 0;
+/// Adds a `deposit` that was already received to the wallet of `player`.
+fn top_up<S: Storage>(
+    storage: &mut S,
+    mut table: Table,
+    player: &HumanAddr,
+    deposit: Uint128,
+) -> HandleResult {
+    let seat = match table.seat_of(player) {
+        Some(seat) => seat,
+        None => return Err(ContractError::NotAPlayer.into()),
+    };
 
-            table.player_a_hand = vec![];
-            table.player_b_hand = vec![];
+    if !table.stage.no_more_action() && table.players[seat].is_contending() {
+        return Err(ContractError::HandInProgress.into());
+    }
 
-            table.player_a_wants_rematch = false;
-            table.player_b_wants_rematch = false;
+    can_deposit(&table, table.players[seat].wallet, deposit)?;
+    table.players[seat].wallet = add_chips(table.players[seat].wallet, deposit)?;
+    table.emit(Event::TopUp {
+        seat: table.position(seat),
+        amount: deposit,
+    });
 
-            deps.storage
-                .set(b"table", &serde_json::to_vec(&table).unwrap());
+    save_table(storage, &table)?;
 
-            Ok(HandleResponse::default())
-        }
-    };
+    Ok(HandleResponse {
+        messages: vec![],
+        log: table.log,
+        data: None,
+    })
 }
 
 impl Table {
-    fn goto_next_stage<S: Storage, A: Api, Q: Querier>(&mut self, deps: &mut Extern<S, A, Q>) {
-        let deck: Vec<Card> = serde_json::from_slice(&deps.storage.get(b"deck").unwrap()).unwrap();
+    /// Tables that play with a token need the token to tell them about deposits.
+    fn register_receive(&self, env: &Env) -> StdResult<Vec<CosmosMsg>> {
+        match &self.currency {
+            Currency::Native { .. } => Ok(vec![]),
+            Currency::Snip20 { address, code_hash } => Ok(vec![register_receive_msg(
+                address,
+                code_hash,
+                env.contract_code_hash.clone(),
+            )?]),
+        }
+    }
 
-        match self.stage {
-            Stage::PreFlop => {
-                self.stage = Stage::Flop;
-                self.community_cards = vec![
-                    deck[FLOP_FIRST_CARD],
-                    deck[FLOP_SECOND_CARD],
-                    deck[FLOP_THIRD_CARD],
-                ];
+    /// Starts the clock of the player to act.
+    fn record_action(&mut self, env: &Env) {
+        self.last_action_height = env.block.height;
+        self.last_action_time = env.block.time;
+    }
+
+    /// Fails until `action_timeout` blocks went by since the last action.
+    fn check_timeout(&self, env: &Env) -> StdResult<()> {
+        let deadline = self.last_action_height.saturating_add(self.action_timeout);
+        if env.block.height < deadline {
+            return Err(ContractError::TimeoutNotReached {
+                blocks_left: deadline - env.block.height,
             }
-            Stage::Flop => {
-                self.stage = Stage::Turn;
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The next hand waits for `player` to approve it with Rematch.
+    fn blocks_next_hand(&self, player: &Player) -> bool {
+        player.wallet >= self.big_blind && !player.wants_rematch && !player.sitting_out
+    }
+
+    fn emit(&mut self, event: Event) {
+        let logged = LoggedEvent {
+            table: self.id,
+            hand: self.game_counter,
+            event: &event,
+        };
+        self.log
+            .push(log("event", serde_json::to_string(&logged).unwrap()));
+        self.hand_actions.push(event);
+    }
+
+    fn position(&self, seat: usize) -> u8 {
+        self.players[seat].position
+    }
+
+    fn seat_of(&self, address: &HumanAddr) -> Option<usize> {
+        self.players.iter().position(|p| p.address == *address)
+    }
+
+    /// The seat of `address` if it's their turn to act in a running hand.
+    fn acting_seat(&self, address: &HumanAddr) -> StdResult<usize> {
+        if self.stage.no_more_action() {
+            return Err(ContractError::HandNotStarted.into());
+        }
+
+        if self.stage == Stage::WaitingForSecrets {
+            return Err(ContractError::WaitingForSecrets.into());
+        }
+
+        let seat = match self.seat_of(address) {
+            Some(seat) if self.players[seat].in_hand => seat,
+            _ => return Err(ContractError::NotAPlayer.into()),
+        };
+
+        if Some(address.clone()) != self.turn {
+            return Err(ContractError::NotYourTurn.into());
+        }
+
+        Ok(seat)
+    }
+
+    /// The first seat after `from` (going around the table) that matches `predicate`.
+    fn next_seat<F: Fn(&Player) -> bool>(&self, from: usize, predicate: F) -> Option<usize> {
+        let seats = self.players.len();
+        (1..=seats)
+            .map(|i| (from + i) % seats)
+            .find(|&i| predicate(&self.players[i]))
+    }
+
+    fn button_seat(&self) -> Option<usize> {
+        match self.button {
+            Some(button) => self.players.iter().position(|p| p.position == button),
+            None => None,
+        }
+    }
+
+    fn max_bet(&self) -> Uint128 {
+        self.players.iter().map(|p| p.bet).max().unwrap_or_default()
+    }
+
+    fn pot(&self) -> StdResult<Uint128> {
+        let mut pot = Uint128::zero();
+        for player in self.players.iter() {
+            pot = add_chips(pot, player.committed)?;
+        }
+        Ok(pot)
+    }
+
+    fn contenders(&self) -> usize {
+        self.players.iter().filter(|p| p.is_contending()).count()
+    }
+
+    /// Players that can cover the big blind and approved the next hand.
+    fn ready_players(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| p.wallet >= self.big_blind && p.wants_rematch)
+            .count()
+    }
+
+    /// The smallest raise that re-opens the betting: the fixed bet size on fixed-limit tables,
+    /// the last full raise otherwise. A short all-in below it doesn't let those who already
+    /// acted raise again.
+    fn full_raise(&self) -> StdResult<Uint128> {
+        match self.betting_structure {
+            BettingStructure::FixedLimit => self.fixed_limit_bet_size(),
+            _ => Ok(self.last_raise),
+        }
+    }
+
+    /// Checks a raise of `amount` on top of a `to_call` call against the table's betting structure.
+    fn validate_raise(&self, amount: Uint128, to_call: Uint128, all_in: bool) -> StdResult<()> {
+        if amount.is_zero() {
+            return Err(ContractError::ZeroRaise.into());
+        }
+
+        match self.betting_structure {
+            BettingStructure::FixedLimit => {
+                if self.street_bets >= FIXED_LIMIT_MAX_BETS {
+                    return Err(ContractError::BettingCapped.into());
+                }
+
+                let bet_size = self.full_raise()?;
+                // a short all-in is the only raise allowed to be smaller than the bet size
+                if amount > bet_size || (amount < bet_size && !all_in) {
+                    return Err(ContractError::FixedLimitRaise {
+                        size: bet_size.u128(),
+                    }
+                    .into());
+                }
+            }
+            BettingStructure::NoLimit | BettingStructure::PotLimit => {
+                // a short all-in is the only raise allowed to be smaller than the last full raise
+                if amount < self.last_raise && !all_in {
+                    return Err(ContractError::BelowMinRaise {
+                        min: self.last_raise.u128(),
+                    }
+                    .into());
+                }
+
+                if let Some(max_raise) = self.max_raise(to_call)? {
+                    if amount > max_raise {
+                        return Err(ContractError::AboveMaxRaise {
+                            max: max_raise.u128(),
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Small bet for pre-flop and flop, big bet for turn and river.
+    fn fixed_limit_bet_size(&self) -> StdResult<Uint128> {
+        match self.stage {
+            Stage::Turn | Stage::River => mul_chips(self.big_blind, 2),
+            _ => Ok(self.big_blind),
+        }
+    }
+
+    /// The largest raise on top of a `to_call` call, `None` when there is no limit.
+    /// Zero when a fixed-limit street is capped.
+    fn max_raise(&self, to_call: Uint128) -> StdResult<Option<Uint128>> {
+        match self.betting_structure {
+            BettingStructure::NoLimit => Ok(None),
+            BettingStructure::PotLimit => Ok(Some(add_chips(self.pot()?, to_call)?)),
+            BettingStructure::FixedLimit => {
+                if self.street_bets >= FIXED_LIMIT_MAX_BETS {
+                    Ok(Some(Uint128::zero()))
+                } else {
+                    Ok(Some(self.fixed_limit_bet_size()?))
+                }
+            }
+        }
+    }
+
+    /// Starts a new hand with every player that approved it and can cover the big blind.
+    /// The hand is dealt once they all reveal the secrets they committed to.
+    fn begin_hand(&mut self) -> StdResult<()> {
+        if self.ready_players() < 2 {
+            return Err(ContractError::NotEnoughPlayers.into());
+        }
+
+        let big_blind = self.big_blind;
+        for player in self.players.iter_mut() {
+            player.in_hand = player.wallet >= big_blind && player.wants_rematch;
+            player.deal_index = None;
+            player.revealed = false;
+            player.folded = false;
+            player.acted = false;
+            player.bet = Uint128::zero();
+            player.committed = Uint128::zero();
+            player.hand = vec![];
+            player.wants_rematch = false;
+        }
+
+        self.stage = Stage::WaitingForSecrets;
+        self.emit(Event::Stage {
+            stage: Stage::WaitingForSecrets,
+            community_cards: vec![],
+        });
+        self.turn = None;
+        self.community_cards = vec![];
+        self.winners = vec![];
+        self.pots = vec![];
+        self.last_play = Some(String::from("Waiting for players to reveal their secrets"));
+
+        Ok(())
+    }
+
+    /// Deals the hand once every player in it revealed, or cancels it if less than two are left.
+    fn settle_reveals<S: Storage, A: Api, Q: Querier>(
+        &mut self,
+        deps: &mut Extern<S, A, Q>,
+        env: &Env,
+    ) -> StdResult<()> {
+        if self.players.iter().filter(|p| p.in_hand).count() < 2 {
+            for player in self.players.iter_mut() {
+                player.in_hand = false;
+            }
+            self.stage = if self.game_counter == 0 {
+                Stage::WaitingForPlayersToJoin
+            } else {
+                Stage::Ended
+            };
+            self.last_play = Some(String::from(
+                "Not enough players left, the hand was cancelled",
+            ));
+            self.emit(Event::HandCancelled {});
+            return Ok(());
+        }
+
+        if self.players.iter().all(|p| !p.in_hand || p.revealed) {
+            self.start_hand(deps, env)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deals the hand: moves the button, shuffles a fresh deck and posts the blinds.
+    /// The deck is seeded by the secrets of all the players in the hand mixed with the block
+    /// the last secret was revealed in.
+    fn start_hand<S: Storage, A: Api, Q: Querier>(
+        &mut self,
+        deps: &mut Extern<S, A, Q>,
+        env: &Env,
+    ) -> StdResult<()> {
+        self.game_counter += 1;
+
+        let mut dealt = 0;
+        for player in self.players.iter_mut().filter(|p| p.in_hand) {
+            player.deal_index = Some(dealt);
+            dealt += 1;
+        }
+        self.dealt = dealt;
+
+        // the button moves on from its last position
+        let seats = self.players.len();
+        let after_button = match self.button {
+            Some(button) => self
+                .players
+                .iter()
+                .position(|p| p.position > button)
+                .unwrap_or(0),
+            None => 0,
+        };
+        let button = self
+            .next_seat((after_button + seats - 1) % seats, |p| p.in_hand)
+            .unwrap();
+        self.button = Some(self.players[button].position);
+
+        let mut secrets = vec![];
+        let mut combined_secret: Vec<u8> = vec![];
+        for player in self.players.iter().filter(|p| p.in_hand) {
+            let secret = match load_secret(&deps.storage, &player.address) {
+                Some(secret) => secret,
+                None => return Err(ContractError::WaitingForSecrets.into()),
+            };
+            combined_secret.extend(&secret);
+            secrets.push(PlayerSecret {
+                player: player.address.clone(),
+                secret: Binary(secret),
+            });
+        }
+        combined_secret.extend(&self.game_counter.to_be_bytes()); // game counter
+        combined_secret.extend(&env.block.height.to_be_bytes());
+        combined_secret.extend(&env.block.time.to_be_bytes());
+        combined_secret.extend(env.block.chain_id.as_bytes());
+        let seed: [u8; 32] = Sha256::digest(&combined_secret).into();
+
+        let mut rng = ChaChaRng::from_seed(seed);
+        let mut deck: Vec<Card> = Deck::default().into_iter().collect();
+        deck.shuffle(&mut rng);
+
+        save_deck(&mut deps.storage, self.id, &deck);
+
+        // the proof stays private until the hand is over, only the commitment is public
+        let mut committed_deck = seed.to_vec();
+        committed_deck.extend(deck_to_bytes(&deck));
+        let deck_commitment = Binary(Sha256::digest(&committed_deck).to_vec());
+        self.deck_commitment = Some(deck_commitment.clone());
+
+        self.hand_seats = self
+            .players
+            .iter()
+            .filter(|p| p.in_hand)
+            .map(|p| HandSeat {
+                seat: p.position,
+                address: p.address.clone(),
+                stack: p.wallet,
+            })
+            .collect();
+        self.hand_actions = vec![];
+        self.emit(Event::Deal {
+            button: self.position(button),
+            seats: self
+                .players
+                .iter()
+                .filter(|p| p.in_hand)
+                .map(|p| p.position)
+                .collect(),
+            deck_commitment: deck_commitment.clone(),
+        });
+
+        let proof = HandProof {
+            game_counter: self.game_counter,
+            secrets,
+            block_height: env.block.height,
+            block_time: env.block.time,
+            chain_id: env.block.chain_id.clone(),
+            seed: Binary(seed.to_vec()),
+            deck,
+            deck_commitment,
+            recipe: String::from(HAND_PROOF_RECIPE),
+        };
+        save_proof(&mut deps.storage, self.id, &proof)?;
+
+        // the blinds go in before the hole cards are dealt, hand histories list them first
+        self.stage = Stage::PreFlop;
+        self.post_blinds(button)?;
+        self.emit(Event::Stage {
+            stage: Stage::PreFlop,
+            community_cards: vec![],
+        });
+
+        // everyone is all-in from the blinds alone
+        if self.turn.is_none() {
+            self.goto_next_stage(deps)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the blinds from the wallets into the bets and gives the turn to the first
+    /// player after the big blind. Heads-up, the button posts the small blind and acts first pre-flop.
+    fn post_blinds(&mut self, button: usize) -> StdResult<()> {
+        let dealt = self.players.iter().filter(|p| p.in_hand).count();
+        let small_blind_seat = if dealt == 2 {
+            button
+        } else {
+            self.next_seat(button, |p| p.in_hand).unwrap()
+        };
+        let big_blind_seat = self.next_seat(small_blind_seat, |p| p.in_hand).unwrap();
+
+        let small_blind = &mut self.players[small_blind_seat];
+        small_blind.wallet = sub_chips(small_blind.wallet, self.small_blind)?;
+        small_blind.bet = self.small_blind;
+        small_blind.committed = self.small_blind;
+        let big_blind = &mut self.players[big_blind_seat];
+        big_blind.wallet = sub_chips(big_blind.wallet, self.big_blind)?;
+        big_blind.bet = self.big_blind;
+        big_blind.committed = self.big_blind;
+        self.last_raise = self.big_blind;
+        self.street_bets = 1; // the big blind is the first bet pre-flop
+        self.emit(Event::PostBlind {
+            seat: self.position(small_blind_seat),
+            amount: self.small_blind,
+        });
+        self.emit(Event::PostBlind {
+            seat: self.position(big_blind_seat),
+            amount: self.big_blind,
+        });
+
+        self.last_play = Some(format!(
+            "Blinds posted: Seat {} {} credits, Seat {} {} credits",
+            self.players[small_blind_seat].position + 1,
+            self.small_blind,
+            self.players[big_blind_seat].position + 1,
+            self.big_blind
+        ));
+
+        match self.next_seat(big_blind_seat, |p| p.can_act()) {
+            Some(first) => self.turn = Some(self.players[first].address.clone()),
+            None => self.turn = None,
+        }
+
+        Ok(())
+    }
+
+    /// Moves the action along after `seat` acted: ends the hand if everyone else folded,
+    /// goes to the next street if the betting round is over, or passes the turn.
+    fn next_turn<S: Storage, A: Api, Q: Querier>(
+        &mut self,
+        deps: &mut Extern<S, A, Q>,
+        seat: usize,
+    ) -> StdResult<()> {
+        if self.contenders() == 1 {
+            return self.win_uncontested(&mut deps.storage);
+        }
+
+        let max_bet = self.max_bet();
+        let round_over = self
+            .players
+            .iter()
+            .filter(|p| p.can_act())
+            .all(|p| p.acted && p.bet == max_bet);
+
+        if round_over {
+            self.goto_next_stage(deps)?;
+        } else {
+            let next = self.next_seat(seat, |p| p.can_act()).unwrap();
+            self.turn = Some(self.players[next].address.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the part of the highest bet on this street that nobody else matched to its bettor.
+    fn return_uncalled_bet(&mut self) -> StdResult<()> {
+        let max_bet = self.max_bet();
+        let top = self.players.iter().position(|p| p.bet == max_bet).unwrap();
+        let second_bet = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != top)
+            .map(|(_, p)| p.bet)
+            .max()
+            .unwrap_or_default();
+
+        let uncalled = sub_chips(max_bet, second_bet)?;
+        if uncalled.is_zero() {
+            return Ok(());
+        }
+        let player = &mut self.players[top];
+        player.wallet = add_chips(player.wallet, uncalled)?;
+        player.bet = second_bet;
+        player.committed = sub_chips(player.committed, uncalled)?;
+        self.emit(Event::ReturnUncalledBet {
+            seat: self.position(top),
+            amount: uncalled,
+        });
+
+        Ok(())
+    }
+
+    /// Everyone else folded, the last player standing takes the pot without a showdown.
+    fn win_uncontested<S: Storage>(&mut self, storage: &mut S) -> StdResult<()> {
+        let winner = self.players.iter().position(|p| p.is_contending()).unwrap();
+        let address = self.players[winner].address.clone();
+
+        // a bet nobody called isn't part of the pot, and isn't raked
+        if self.players[winner].bet == self.max_bet() {
+            self.return_uncalled_bet()?;
+        }
+        let pot = self.pot()?;
+        let rake = self.rake_pots(&[pot])?[0];
+        let won = sub_chips(pot, rake)?;
+        self.keep_rake(storage, rake)?;
+
+        self.players[winner].wallet = add_chips(self.players[winner].wallet, won)?;
+        self.players[winner].win_counter += 1;
+        self.emit(Event::WinPot {
+            pot: 0,
+            seat: self.position(winner),
+            amount: won,
+        });
+        for player in self.players.iter_mut() {
+            player.bet = Uint128::zero();
+            player.committed = Uint128::zero();
+        }
+
+        self.pots = vec![Pot {
+            amount: pot,
+            rake,
+            eligible: vec![address.clone()],
+            winners: vec![address.clone()],
+        }];
+        self.winners = vec![address];
+        self.stage = Stage::Ended;
+        self.emit(Event::Stage {
+            stage: Stage::Ended,
+            community_cards: self.community_cards.clone(),
+        });
+        self.turn = None;
+
+        self.archive_hand(storage)
+    }
+
+    /// The hole cards of `player`, if the transaction that moved the game counter past
+    /// `dealt_before` dealt them in.
+    fn deal_answer<S: Storage>(
+        &self,
+        storage: &S,
+        dealt_before: u64,
+        player: &HumanAddr,
+    ) -> StdResult<Option<Binary>> {
+        if self.game_counter == dealt_before {
+            return Ok(None);
+        }
+
+        let seat = match self.seat_of(player) {
+            Some(seat) if self.players[seat].in_hand => seat,
+            _ => return Ok(None),
+        };
+
+        let deck = load_deck(storage, self.id)?;
+
+        Ok(Some(to_binary(&HandleAnswer::Dealt {
+            game_counter: self.game_counter,
+            hand: self.hole_cards(&deck, seat),
+        })?))
+    }
+
+    /// Hole cards dealt to `seat` in the current hand.
+    fn hole_cards(&self, deck: &[Card], seat: usize) -> Vec<Card> {
+        let index = self.players[seat].deal_index.unwrap() as usize;
+        vec![deck[index], deck[self.dealt as usize + index]]
+    }
+
+    fn board_card(&self, deck: &[Card], offset: usize) -> Card {
+        deck[2 * self.dealt as usize + offset]
+    }
+
+    fn goto_next_stage<S: Storage, A: Api, Q: Querier>(
+        &mut self,
+        deps: &mut Extern<S, A, Q>,
+    ) -> StdResult<()> {
+        let deck = load_deck(&deps.storage, self.id)?;
+
+        self.return_uncalled_bet()?;
+
+        match self.stage {
+            Stage::PreFlop => {
+                self.stage = Stage::Flop;
                 self.community_cards = vec![
-                    deck[FLOP_FIRST_CARD],
-                    deck[FLOP_SECOND_CARD],
-                    deck[FLOP_THIRD_CARD],
-                    deck[TURN_CARD],
+                    self.board_card(&deck, FLOP_FIRST_CARD),
+                    self.board_card(&deck, FLOP_SECOND_CARD),
+                    self.board_card(&deck, FLOP_THIRD_CARD),
                 ];
             }
+            Stage::Flop => {
+                self.stage = Stage::Turn;
+                let turn_card = self.board_card(&deck, TURN_CARD);
+                self.community_cards.push(turn_card);
+            }
             Stage::Turn => {
                 self.stage = Stage::River;
-                self.community_cards = vec![
-                    deck[FLOP_FIRST_CARD],
-                    deck[FLOP_SECOND_CARD],
-                    deck[FLOP_THIRD_CARD],
-                    deck[TURN_CARD],
-                    deck[RIVER_CARD],
-                ];
+                let river_card = self.board_card(&deck, RIVER_CARD);
+                self.community_cards.push(river_card);
             }
             Stage::River => {
-                let mut player_a_7_card_hand = self.community_cards.clone();
-                player_a_7_card_hand
-                    .extend(vec![deck[PLAYER_A_FIRST_CARD], deck[PLAYER_A_SECOND_CARD]]);
-                let player_a_rank = player_a_7_card_hand.rank();
-
-                let mut player_b_7_card_hand = self.community_cards.clone();
-                player_b_7_card_hand
-                    .extend(vec![deck[PLAYER_B_FIRST_CARD], deck[PLAYER_B_SECOND_CARD]]);
-                let player_b_rank = player_b_7_card_hand.rank();
-
-                if player_a_rank > player_b_rank {
-                    self.stage = Stage::EndedWinnerA;
-                    self.player_a_wallet += (self.player_a_bet + self.player_b_bet) as i64;
-                    self.player_a_win_counter += 1;
-                } else if player_a_rank < player_b_rank {
-                    self.stage = Stage::EndedWinnerB;
-                    self.player_b_wallet += (self.player_a_bet + self.player_b_bet) as i64;
-                    self.player_b_win_counter += 1;
-                } else {
-                    self.stage = Stage::EndedDraw;
-                    self.player_a_wallet += (self.player_a_bet) as i64;
-                    self.player_b_wallet += (self.player_b_bet) as i64;
-                    self.tie_counter += 1;
-                }
-
-                self.player_a_bet = 0;
-                self.player_b_bet = 0;
-
-                self.player_a_hand = vec![deck[PLAYER_A_FIRST_CARD], deck[PLAYER_A_SECOND_CARD]];
-                self.player_b_hand = vec![deck[PLAYER_B_FIRST_CARD], deck[PLAYER_B_SECOND_CARD]];
-                return;
+                return self.showdown(&mut deps.storage, &deck);
             }
             Stage::WaitingForPlayersToJoin => {
-                return;
+                return Ok(());
             }
-            Stage::EndedWinnerA => {
-                return;
+            Stage::WaitingForSecrets => {
+                return Ok(());
             }
-            Stage::EndedWinnerB => {
-                return;
+            Stage::Ended => {
+                return Ok(());
             }
-            Stage::EndedDraw => {
-                return;
+        }
+
+        self.emit(Event::Stage {
+            stage: self.stage.clone(),
+            community_cards: self.community_cards.clone(),
+        });
+
+        for player in self.players.iter_mut() {
+            player.acted = false;
+            player.bet = Uint128::zero();
+        }
+        self.last_raise = self.big_blind;
+        self.street_bets = 0;
+
+        // after the flop the first player after the button acts first and the button acts last
+        let button = self.button_seat().unwrap();
+        let first = self.next_seat(button, |p| p.can_act());
+        self.turn = first.map(|first| self.players[first].address.clone());
+
+        // Turn ended with at most one player that still has cash, just play it out
+        if self.players.iter().filter(|p| p.can_act()).count() <= 1 {
+            while self.stage != Stage::Ended {
+                self.goto_next_stage(deps)?;
             }
         }
 
-        // Turn ended with both player out of cash, just play it out
-        if self.player_a_wallet == 0 || self.player_b_wallet == 0 {
-            while self.stage != Stage::EndedDraw
-                && self.stage != Stage::EndedWinnerA
-                && self.stage != Stage::EndedWinnerB
-            {
-                self.goto_next_stage(deps);
+        Ok(())
+    }
+
+    /// Splits everything committed in this hand into a main pot and side pots. Each pot is
+    /// contested by the players that are still in the hand and committed at least its level.
+    fn build_pots(&self) -> StdResult<Vec<(Uint128, Vec<usize>)>> {
+        let mut levels: Vec<Uint128> = self
+            .players
+            .iter()
+            .filter(|p| p.is_contending())
+            .map(|p| p.committed)
+            .collect();
+        levels.sort();
+        levels.dedup();
+
+        let mut pots = vec![];
+        let mut assigned = Uint128::zero();
+        let mut previous_level = Uint128::zero();
+        for level in levels {
+            let mut amount = Uint128::zero();
+            for player in self.players.iter() {
+                let share = sub_chips(
+                    player.committed.min(level),
+                    player.committed.min(previous_level),
+                )?;
+                amount = add_chips(amount, share)?;
             }
-            return;
+            let eligible: Vec<usize> = (0..self.players.len())
+                .filter(|&seat| {
+                    self.players[seat].is_contending() && self.players[seat].committed >= level
+                })
+                .collect();
+
+            assigned = add_chips(assigned, amount)?;
+            pots.push((amount, eligible));
+            previous_level = level;
         }
+
+        // chips folded above the highest level still in the hand go to the last pot
+        let folded = sub_chips(self.pot()?, assigned)?;
+        if let Some(last) = pots.last_mut() {
+            last.0 = add_chips(last.0, folded)?;
+        }
+
+        Ok(pots)
+    }
+
+    /// The rake taken from each of the pots of the hand that just ended, the main pot is raked
+    /// first until the cap for the hand is reached.
+    fn rake_pots(&self, pots: &[Uint128]) -> StdResult<Vec<Uint128>> {
+        let rake = match &self.rake {
+            Some(rake) if !(rake.no_flop_no_drop && self.community_cards.is_empty()) => rake,
+            _ => return Ok(vec![Uint128::zero(); pots.len()]),
+        };
+
+        let mut cap = match rake.cap {
+            Some(cap) => Some(mul_chips(self.big_blind, cap as u128)?),
+            None => None,
+        };
+        let mut rakes = vec![];
+        for &amount in pots {
+            let mut pot_rake =
+                Uint128(mul_chips(amount, rake.basis_points as u128)?.u128() / 10_000);
+            if let Some(left) = cap {
+                pot_rake = pot_rake.min(left);
+                cap = Some(sub_chips(left, pot_rake)?);
+            }
+            rakes.push(pot_rake);
+        }
+        Ok(rakes)
+    }
+
+    /// Keeps `rake` for the collector, listing the table among the ones to collect from.
+    fn keep_rake<S: Storage>(&mut self, storage: &mut S, rake: Uint128) -> StdResult<()> {
+        if rake.is_zero() {
+            return Ok(());
+        }
+        if self.uncollected_rake.is_zero() {
+            let mut raked_tables = load_raked_tables(storage)?;
+            raked_tables.push(self.id);
+            save_raked_tables(storage, &raked_tables)?;
+        }
+        self.uncollected_rake = add_chips(self.uncollected_rake, rake)?;
+        Ok(())
+    }
+
+    fn showdown<S: Storage>(&mut self, storage: &mut S, deck: &[Card]) -> StdResult<()> {
+        let mut ranks = vec![];
+        for seat in 0..self.players.len() {
+            if !self.players[seat].is_contending() {
+                continue;
+            }
+
+            let hole_cards = self.hole_cards(deck, seat);
+            let mut seven_card_hand = self.community_cards.clone();
+            seven_card_hand.extend(hole_cards.iter());
+            ranks.push((seat, seven_card_hand.rank()));
+
+            self.emit(Event::Show {
+                seat: self.position(seat),
+                hand: hole_cards.clone(),
+            });
+            self.players[seat].hand = hole_cards;
+        }
+
+        let button = self.button_seat().unwrap();
+        let seats = self.players.len();
+
+        let built = self.build_pots()?;
+        let amounts: Vec<Uint128> = built.iter().map(|(amount, _)| *amount).collect();
+        let rakes = self.rake_pots(&amounts)?;
+
+        let mut pots = vec![];
+        let mut winners: Vec<usize> = vec![];
+        for (pot, (amount, eligible)) in built.into_iter().enumerate() {
+            let rake = rakes[pot];
+            let prize = sub_chips(amount, rake)?;
+            self.keep_rake(storage, rake)?;
+
+            let best = ranks
+                .iter()
+                .filter(|(seat, _)| eligible.contains(seat))
+                .map(|(_, rank)| rank)
+                .max()
+                .unwrap();
+            // odd chips go to the winners closest to the left of the button
+            let mut pot_winners: Vec<usize> = ranks
+                .iter()
+                .filter(|(seat, rank)| eligible.contains(seat) && rank == best)
+                .map(|(seat, _)| *seat)
+                .collect();
+            pot_winners.sort_by_key(|&seat| (seat + seats - button - 1) % seats);
+
+            let share = prize.u128() / pot_winners.len() as u128;
+            let odd_chips = prize.u128() % pot_winners.len() as u128;
+            for (i, &seat) in pot_winners.iter().enumerate() {
+                let odd_chip = if (i as u128) < odd_chips { 1 } else { 0 };
+                let won = Uint128(share + odd_chip);
+                self.players[seat].wallet = add_chips(self.players[seat].wallet, won)?;
+                self.emit(Event::WinPot {
+                    pot: pot as u8,
+                    seat: self.position(seat),
+                    amount: won,
+                });
+            }
+
+            for &seat in pot_winners.iter() {
+                if !winners.contains(&seat) {
+                    winners.push(seat);
+                }
+            }
+            pots.push(Pot {
+                amount,
+                rake,
+                eligible: eligible
+                    .iter()
+                    .map(|&seat| self.players[seat].address.clone())
+                    .collect(),
+                winners: pot_winners
+                    .iter()
+                    .map(|&seat| self.players[seat].address.clone())
+                    .collect(),
+            });
+        }
+
+        for &seat in winners.iter() {
+            self.players[seat].win_counter += 1;
+        }
+        if pots.iter().any(|pot| pot.winners.len() > 1) {
+            self.tie_counter += 1;
+        }
+
+        for player in self.players.iter_mut() {
+            player.bet = Uint128::zero();
+            player.committed = Uint128::zero();
+        }
+
+        winners.sort();
+        self.winners = winners
+            .iter()
+            .map(|&seat| self.players[seat].address.clone())
+            .collect();
+        self.pots = pots;
+        self.stage = Stage::Ended;
+        self.emit(Event::Stage {
+            stage: Stage::Ended,
+            community_cards: self.community_cards.clone(),
+        });
+        self.turn = None;
+
+        self.archive_hand(storage)
+    }
+
+    /// Appends the hand that just ended to the hand history.
+    fn archive_hand<S: Storage>(&mut self, storage: &mut S) -> StdResult<()> {
+        let proof = match load_proof(storage, self.id, self.game_counter)? {
+            Some(proof) => proof,
+            None => return Err(ContractError::HandNotFound.into()),
+        };
+
+        let history = HandHistory {
+            table_id: self.id,
+            game_counter: self.game_counter,
+            max_seats: self.max_seats,
+            big_blind: self.big_blind,
+            small_blind: self.small_blind,
+            betting_structure: self.betting_structure,
+            seats: std::mem::take(&mut self.hand_seats),
+            actions: std::mem::take(&mut self.hand_actions),
+            community_cards: self.community_cards.clone(),
+            pots: self.pots.clone(),
+            winners: self.winners.clone(),
+            proof,
+        };
+        save_history(storage, &history)
     }
 }
 
@@ -722,56 +2318,2022 @@ impl Table {
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    GetMyHand { secret: u64 },
-    GetPublicData {},
+    GetMyHand {
+        address: HumanAddr,
+        key: String,
+    },
+    // table_id defaults to table 1, the room created with CreateRoom
+    GetPublicData {
+        table_id: Option<u64>,
+    },
+    GetHandProof {
+        table_id: Option<u64>,
+        game_counter: u64,
+    },
+    GetHandHistory {
+        table_id: Option<u64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    }, // oldest hands first
+    ListTables {
+        filter: Option<TableFilter>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    }, // by table id
+}
+
+/// Tables listed by `ListTables` match every setting that is set.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct TableFilter {
+    pub big_blind: Option<Uint128>,
+    pub betting_structure: Option<BettingStructure>,
+    pub currency: Option<Currency>,
+    pub free_seat: Option<bool>,
+}
+
+const DEFAULT_HISTORY_LIMIT: u32 = 10;
+const MAX_HISTORY_LIMIT: u32 = 30;
+const DEFAULT_TABLES_LIMIT: u32 = 10;
+const MAX_TABLES_LIMIT: u32 = 30;
+
+const HAND_PROOF_RECIPE: &str = "seed = SHA-256(secrets in seat order || game_counter as u64 big-endian || block_height as u64 big-endian || block_time as u64 big-endian || chain_id as UTF-8); \
+deck = rs_poker Deck::default() collected in iteration order, then rand 0.7 SliceRandom::shuffle with rand_chacha 0.2 ChaChaRng::from_seed(seed); \
+deck_commitment = SHA-256(seed || one byte per card of the deck, value * 4 + suit, values Two = 0 to Ace = 12, suits Spade = 0, Club = 1, Heart = 2, Diamond = 3)";
+
+/// Everything needed to recompute the shuffle of a finished hand and check it against
+/// the deck commitment that was published when the hand was dealt.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HandProof {
+    pub game_counter: u64,
+    pub secrets: Vec<PlayerSecret>,
+    pub block_height: u64,
+    pub block_time: u64,
+    pub chain_id: String,
+    pub seed: Binary,
+    pub deck: Vec<Card>,
+    pub deck_commitment: Binary,
+    pub recipe: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerSecret {
+    pub player: HumanAddr,
+    pub secret: Binary,
+}
+
+/// A finished hand, from the deal to the payouts.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HandHistory {
+    pub table_id: u64,
+    pub game_counter: u64,
+    pub max_seats: u8,
+    pub big_blind: Uint128,
+    pub small_blind: Uint128,
+    pub betting_structure: BettingStructure,
+    pub seats: Vec<HandSeat>,
+    pub actions: Vec<Event>, // hole cards only show up in the show events of a showdown
+    pub community_cards: Vec<Card>,
+    pub pots: Vec<Pot>,
+    pub winners: Vec<HumanAddr>,
+    pub proof: HandProof,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HandSeat {
+    pub seat: u8,
+    pub address: HumanAddr,
+    pub stack: Uint128, // before the blinds
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HandHistoryPage {
+    pub hands: Vec<HandHistory>,
+}
+
+/// What players need to pick a table in the lobby.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TableSummary {
+    pub table_id: u64,
+    pub big_blind: Uint128,
+    pub small_blind: Uint128,
+    pub betting_structure: BettingStructure,
+    pub currency: Currency,
+    pub rake: Option<Rake>,
+    pub min_credit: Uint128,
+    pub max_credit: Uint128,
+    pub max_seats: u8,
+    pub players: u8,
+    pub stage: Stage,
+    pub game_counter: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TableList {
+    pub tables: Vec<TableSummary>,
+}
+
+impl TableFilter {
+    fn matches(&self, table: &Table) -> bool {
+        let free_seat = table.players.len() < table.max_seats as usize;
+        self.big_blind
+            .is_none_or(|big_blind| table.big_blind == big_blind)
+            && self
+                .betting_structure
+                .is_none_or(|structure| table.betting_structure == structure)
+            && self
+                .currency
+                .as_ref()
+                .is_none_or(|currency| table.currency == *currency)
+            && self.free_seat.is_none_or(|wanted| free_seat == wanted)
+    }
 }
 
 pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
     match msg {
-        QueryMsg::GetPublicData {} => {
-            return Ok(Binary(deps.storage.get(b"table").unwrap()));
+        QueryMsg::GetPublicData { table_id } => {
+            let table_id = table_id.unwrap_or(DEFAULT_TABLE_ID);
+            to_binary(&load_table(&deps.storage, table_id)?)
         }
-        QueryMsg::GetMyHand { secret } => {
-            let secret_bytes = secret.to_be_bytes().to_vec();
+        QueryMsg::GetHandProof {
+            table_id,
+            game_counter,
+        } => {
+            let table = load_table(&deps.storage, table_id.unwrap_or(DEFAULT_TABLE_ID))?;
+
+            let in_progress = matches!(
+                table.stage,
+                Stage::PreFlop | Stage::Flop | Stage::Turn | Stage::River
+            );
+            if game_counter > table.game_counter
+                || (game_counter == table.game_counter && in_progress)
+            {
+                return Err(ContractError::HandProofNotAvailable.into());
+            }
+
+            match load_proof(&deps.storage, table.id, game_counter)? {
+                Some(proof) => to_binary(&proof),
+                None => Err(ContractError::HandNotFound.into()),
+            }
+        }
+        QueryMsg::GetHandHistory {
+            table_id,
+            start_after,
+            limit,
+        } => {
+            let table = load_table(&deps.storage, table_id.unwrap_or(DEFAULT_TABLE_ID))?;
+
+            let limit = limit
+                .unwrap_or(DEFAULT_HISTORY_LIMIT)
+                .min(MAX_HISTORY_LIMIT) as usize;
+            let first = start_after.unwrap_or(0).saturating_add(1);
+
+            // the hand in progress isn't in the history yet
+            let mut hands = vec![];
+            for game_counter in first..=table.game_counter {
+                if hands.len() == limit {
+                    break;
+                }
+                if let Some(history) = load_history(&deps.storage, table.id, game_counter)? {
+                    hands.push(history);
+                }
+            }
 
-            let player_a_secret = match deps.storage.get(b"player_a_secret") {
-                None => {
-                    return Err(StdError::generic_err(
-                        "You are not a player, but there are still two seats left.",
-                    ))
+            Ok(Binary(
+                serde_json::to_vec(&HandHistoryPage { hands }).unwrap(),
+            ))
+        }
+        QueryMsg::ListTables {
+            filter,
+            start_after,
+            limit,
+        } => {
+            let lobby = load_lobby(&deps.storage)?;
+            let filter = filter.unwrap_or_default();
+
+            let limit = limit.unwrap_or(DEFAULT_TABLES_LIMIT).min(MAX_TABLES_LIMIT) as usize;
+            let first = start_after.unwrap_or(0).saturating_add(1);
+
+            let mut tables = vec![];
+            for table_id in first..=lobby.table_count {
+                if tables.len() == limit {
+                    break;
+                }
+                let table = load_table(&deps.storage, table_id)?;
+                if filter.matches(&table) {
+                    tables.push(TableSummary {
+                        table_id: table.id,
+                        big_blind: table.big_blind,
+                        small_blind: table.small_blind,
+                        betting_structure: table.betting_structure,
+                        currency: table.currency,
+                        rake: table.rake,
+                        min_credit: table.min_credit,
+                        max_credit: table.max_credit,
+                        max_seats: table.max_seats,
+                        players: table.players.len() as u8,
+                        stage: table.stage,
+                        game_counter: table.game_counter,
+                    });
                 }
-                Some(x) => x,
+            }
+
+            Ok(Binary(serde_json::to_vec(&TableList { tables }).unwrap()))
+        }
+        QueryMsg::GetMyHand { address, key } => {
+            if !check_viewing_key(&deps.storage, &address, &key) {
+                return Err(ContractError::WrongViewingKey.into());
+            }
+
+            let table = player_table(&deps.storage, &address)?;
+            let seat = table.seat_of(&address).unwrap();
+
+            if table.stage == Stage::WaitingForPlayersToJoin
+                || table.stage == Stage::WaitingForSecrets
+                || !table.players[seat].in_hand
+            {
+                return Err(ContractError::NotDealtIn.into());
+            }
+
+            let deck = load_deck(&deps.storage, table.id)?;
+
+            Ok(Binary(
+                serde_json::to_vec(&table.hole_cards(&deck, seat)).unwrap(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pokerstars::export_hand;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{coins, Coin, ReadonlyStorage, StdError};
+    use proptest::prelude::*;
+    use rs_poker::core::{Suit, Value};
+    use std::collections::{BTreeMap, HashMap};
+
+    type Deps = Extern<MockStorage, MockApi, MockQuerier>;
+
+    const BIG_BLIND: u128 = 10;
+    const BUY_IN: u128 = 1000;
+
+    fn secret(player: &str, hand: u64) -> Binary {
+        Binary(format!("{}'s secret for hand {}", player, hand).into_bytes())
+    }
+
+    fn commitment(player: &str, hand: u64) -> Binary {
+        Binary(Sha256::digest(secret(player, hand).as_slice()).to_vec())
+    }
+
+    fn card(value: Value, suit: Suit) -> Card {
+        Card { value, suit }
+    }
+
+    fn table<S: Storage>(deps: &Extern<S, MockApi, MockQuerier>) -> Table {
+        load_table(&deps.storage, DEFAULT_TABLE_ID).unwrap()
+    }
+
+    fn wallet(deps: &Deps, player: &str) -> u128 {
+        let table = table(deps);
+        table.players[table.seat_of(&HumanAddr::from(player)).unwrap()]
+            .wallet
+            .u128()
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: StdResult<T>, expected: ContractError) {
+        match result {
+            Err(err) => assert_eq!(err.to_string(), StdError::from(expected).to_string()),
+            Ok(ok) => panic!("expected {:?}, got {:?}", expected, ok),
+        }
+    }
+
+    fn act(deps: &mut Deps, player: &str, msg: HandleMsg) -> HandleResponse {
+        handle(deps, mock_env(player, &[]), msg).unwrap()
+    }
+
+    fn create_room() -> Deps {
+        open_room(None, None)
+    }
+
+    fn open_room(betting_structure: Option<BettingStructure>, max_seats: Option<u8>) -> Deps {
+        open_raked_room(betting_structure, max_seats, None)
+    }
+
+    fn create_raked_room(rake: Option<Rake>) -> Deps {
+        open_raked_room(None, None, rake)
+    }
+
+    fn open_raked_room(
+        betting_structure: Option<BettingStructure>,
+        max_seats: Option<u8>,
+        rake: Option<Rake>,
+    ) -> Deps {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure,
+            max_seats,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+            rake,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        init(&mut deps, mock_env("creator", &[]), msg).unwrap();
+        deps
+    }
+
+    /// Seats `players` with their buy-ins in seat order and gets the first hand dealt
+    /// to all of them. The first player has the button.
+    fn deal_at(mut deps: Deps, players: &[(&str, u128)]) -> Deps {
+        for (player, buy_in) in players {
+            let msg = HandleMsg::Join {
+                commitment: commitment(player, 1),
+            };
+            handle(&mut deps, mock_env(*player, &coins(*buy_in, "uscrt")), msg).unwrap();
+        }
+        for (player, _) in players {
+            let msg = HandleMsg::Reveal {
+                secret: secret(player, 1),
             };
-            let player_b_secret = match deps.storage.get(b"player_b_secret") {
-                None => {
-                    return Err(StdError::generic_err(
-                        "You are not a player, but there is still one seat left.",
-                    ))
+            act(&mut deps, player, msg);
+        }
+        deps
+    }
+
+    /// Deals the first hand at a no-limit room with a seat for each of `players`.
+    fn deal(players: &[(&str, u128)]) -> Deps {
+        deal_at(open_room(None, Some(players.len() as u8)), players)
+    }
+
+    /// A rake collected by the house, that doesn't rake hands ending before the flop.
+    fn house_rake(basis_points: u16, cap: Option<u32>) -> Rake {
+        Rake {
+            basis_points,
+            cap,
+            no_flop_no_drop: true,
+            collector: HumanAddr::from("house"),
+        }
+    }
+
+    /// Alice (seat 0, button and small blind) and Bob (seat 1, big blind) sit down
+    /// and get the first hand dealt.
+    fn deal_heads_up() -> Deps {
+        deal(&[("alice", BUY_IN), ("bob", BUY_IN)])
+    }
+
+    fn seat_heads_up(deps: Deps) -> Deps {
+        deal_at(deps, &[("alice", BUY_IN), ("bob", BUY_IN)])
+    }
+
+    /// Rearranges the deck of the hand in progress so the players in it get `hands`,
+    /// in seat order, and the board runs out `board`.
+    fn stack_deck(deps: &mut Deps, hands: &[[Card; 2]], board: [Card; 5]) {
+        let dealt = hands.len();
+        let mut wanted = vec![];
+        for (i, hand) in hands.iter().enumerate() {
+            wanted.push((i, hand[0]));
+            wanted.push((dealt + i, hand[1]));
+        }
+        let offsets = [
+            FLOP_FIRST_CARD,
+            FLOP_SECOND_CARD,
+            FLOP_THIRD_CARD,
+            TURN_CARD,
+            RIVER_CARD,
+        ];
+        for (offset, card) in offsets.iter().zip(board.iter()) {
+            wanted.push((2 * dealt + offset, *card));
+        }
+
+        let mut deck: Vec<Card> = Deck::default().into_iter().collect();
+        for (index, card) in wanted {
+            let from = deck.iter().position(|c| *c == card).unwrap();
+            deck.swap(index, from);
+        }
+        save_deck(&mut deps.storage, DEFAULT_TABLE_ID, &deck);
+    }
+
+    /// Alice has pocket aces and Bob pocket kings on a dry board.
+    fn aces_against_kings(deps: &mut Deps) {
+        stack_deck(
+            deps,
+            &[
+                [card(Value::Ace, Suit::Spade), card(Value::Ace, Suit::Heart)],
+                [
+                    card(Value::King, Suit::Spade),
+                    card(Value::King, Suit::Heart),
+                ],
+            ],
+            [
+                card(Value::Two, Suit::Club),
+                card(Value::Seven, Suit::Diamond),
+                card(Value::Nine, Suit::Heart),
+                card(Value::Jack, Suit::Spade),
+                card(Value::Three, Suit::Club),
+            ],
+        );
+    }
+
+    #[test]
+    fn create_room_sets_buy_in_limits() {
+        let deps = create_room();
+        let table = table(&deps);
+
+        assert!(table.stage == Stage::WaitingForPlayersToJoin);
+        assert_eq!(table.max_seats, 2);
+        assert_eq!(table.small_blind, Uint128(5));
+        assert_eq!(table.min_buy_in, DEFAULT_MIN_BUY_IN);
+        assert_eq!(table.max_buy_in, DEFAULT_MAX_BUY_IN);
+        assert_eq!(table.min_credit, Uint128(BIG_BLIND * 20));
+        assert_eq!(table.max_credit, Uint128(BIG_BLIND * 100));
+    }
+
+    #[test]
+    fn create_room_rejects_bad_settings() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(1),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert_error(
+            init(&mut deps, mock_env("creator", &[]), msg),
+            ContractError::InvalidBigBlind,
+        );
+
+        // the small blind is half of it
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(15),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert_error(
+            init(&mut deps, mock_env("creator", &[]), msg),
+            ContractError::InvalidBigBlind,
+        );
+
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: Some(10),
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert_error(
+            init(&mut deps, mock_env("creator", &[]), msg),
+            ContractError::InvalidSeats {
+                min: MIN_SEATS,
+                max: MAX_SEATS,
+            },
+        );
+
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: Some(250),
+            max_buy_in: Some(40),
+            currency: None,
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert_error(
+            init(&mut deps, mock_env("creator", &[]), msg),
+            ContractError::InvalidBuyIn,
+        );
+
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+            rake: Some(house_rake(MAX_RAKE_BASIS_POINTS + 1, None)),
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert_error(
+            init(&mut deps, mock_env("creator", &[]), msg),
+            ContractError::InvalidRake {
+                max: MAX_RAKE_BASIS_POINTS,
+            },
+        );
+
+        // the rake couldn't be paid out to a collector that isn't an address
+        let msg = InitMsg::CreateLobby {
+            rake: Some(Rake {
+                collector: HumanAddr::from(""),
+                ..house_rake(500, None)
+            }),
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        assert!(init(&mut deps, mock_env("creator", &[]), msg).is_err());
+    }
+
+    #[test]
+    fn deep_stack_tables_take_bigger_buy_ins() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: Some(40),
+            max_buy_in: Some(250),
+            currency: None,
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        init(&mut deps, mock_env("creator", &[]), msg).unwrap();
+
+        let public_data = query(&deps, QueryMsg::GetPublicData { table_id: None }).unwrap();
+        let table: Table = serde_json::from_slice(&public_data).unwrap();
+        assert_eq!((table.min_buy_in, table.max_buy_in), (40, 250));
+
+        let join = HandleMsg::Join {
+            commitment: commitment("alice", 1),
+        };
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(300, "uscrt")),
+                join.clone(),
+            ),
+            ContractError::BelowMinBuyIn { min: 400 },
+        );
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(3000, "uscrt")),
+                join.clone(),
+            ),
+            ContractError::AboveMaxBuyIn { max: 2500 },
+        );
+        handle(&mut deps, mock_env("alice", &coins(2500, "uscrt")), join).unwrap();
+    }
+
+    #[test]
+    fn errors_carry_a_stable_code() {
+        let err = StdError::from(ContractError::NotYourTurn);
+        assert!(err.to_string().contains(r#""code":"not_your_turn""#));
+    }
+
+    #[test]
+    fn join_checks_the_deposit() {
+        let mut deps = create_room();
+        let join = |player: &str| HandleMsg::Join {
+            commitment: commitment(player, 1),
+        };
+
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), join("alice")),
+            ContractError::NoFunds,
+        );
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(BUY_IN, "uatom")),
+                join("alice"),
+            ),
+            ContractError::WrongDenom {
+                expected: String::from("uscrt"),
+            },
+        );
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(100, "uscrt")),
+                join("alice"),
+            ),
+            ContractError::BelowMinBuyIn { min: 200 },
+        );
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(1001, "uscrt")),
+                join("alice"),
+            ),
+            ContractError::AboveMaxBuyIn { max: 1000 },
+        );
+
+        handle(
+            &mut deps,
+            mock_env("alice", &coins(BUY_IN, "uscrt")),
+            join("alice"),
+        )
+        .unwrap();
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(BUY_IN, "uscrt")),
+                join("alice"),
+            ),
+            ContractError::AlreadySeated,
+        );
+        assert!(table(&deps).stage == Stage::WaitingForPlayersToJoin);
+
+        handle(
+            &mut deps,
+            mock_env("bob", &coins(BUY_IN, "uscrt")),
+            join("bob"),
+        )
+        .unwrap();
+        assert!(table(&deps).stage == Stage::WaitingForSecrets);
+
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("carol", &coins(BUY_IN, "uscrt")),
+                join("carol"),
+            ),
+            ContractError::TableFull,
+        );
+    }
+
+    #[test]
+    fn the_first_hand_starts_once_two_players_are_seated() {
+        let mut deps = open_room(None, Some(4));
+        let join = |deps: &mut Deps, player: &str| {
+            let msg = HandleMsg::Join {
+                commitment: commitment(player, 1),
+            };
+            handle(deps, mock_env(player, &coins(BUY_IN, "uscrt")), msg).unwrap();
+        };
+        let reveal = |deps: &mut Deps, player: &str| {
+            let msg = HandleMsg::Reveal {
+                secret: secret(player, 1),
+            };
+            act(deps, player, msg);
+        };
+
+        join(&mut deps, "alice");
+        assert!(table(&deps).stage == Stage::WaitingForPlayersToJoin);
+        join(&mut deps, "bob");
+        assert!(table(&deps).stage == Stage::WaitingForSecrets);
+
+        // carol makes it before all the secrets are revealed, dave doesn't
+        reveal(&mut deps, "alice");
+        join(&mut deps, "carol");
+        reveal(&mut deps, "bob");
+        assert!(table(&deps).stage == Stage::WaitingForSecrets);
+        reveal(&mut deps, "carol");
+        join(&mut deps, "dave");
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::PreFlop);
+        assert_eq!(table.dealt, 3);
+        assert!(table.players[..3].iter().all(|p| p.in_hand));
+        assert!(!table.players[3].in_hand);
+        assert_eq!(table.turn, Some(HumanAddr::from("alice")));
+    }
+
+    #[test]
+    fn reveals_deal_the_hand_and_post_the_blinds() {
+        let mut deps = create_room();
+        for player in &["alice", "bob"] {
+            let msg = HandleMsg::Join {
+                commitment: commitment(player, 1),
+            };
+            handle(&mut deps, mock_env(*player, &coins(BUY_IN, "uscrt")), msg).unwrap();
+        }
+
+        let wrong_secret = HandleMsg::Reveal {
+            secret: secret("bob", 1),
+        };
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), wrong_secret),
+            ContractError::SecretMismatch,
+        );
+
+        let response = act(
+            &mut deps,
+            "alice",
+            HandleMsg::Reveal {
+                secret: secret("alice", 1),
+            },
+        );
+        assert_eq!(response.data, None);
+        assert!(table(&deps).stage == Stage::WaitingForSecrets);
+
+        let response = act(
+            &mut deps,
+            "bob",
+            HandleMsg::Reveal {
+                secret: secret("bob", 1),
+            },
+        );
+        let table = table(&deps);
+        assert!(table.stage == Stage::PreFlop);
+        assert_eq!(table.game_counter, 1);
+        assert_eq!(table.button, Some(0));
+        assert_eq!(table.turn, Some(HumanAddr::from("alice")));
+        assert_eq!(table.players[0].wallet, Uint128(995));
+        assert_eq!(table.players[1].wallet, Uint128(990));
+
+        let deck = load_deck(&deps.storage, DEFAULT_TABLE_ID).unwrap();
+        let dealt: HandleAnswer = serde_json::from_slice(&response.data.unwrap()).unwrap();
+        assert!(
+            dealt
+                == HandleAnswer::Dealt {
+                    game_counter: 1,
+                    hand: table.hole_cards(&deck, 1),
                 }
-                Some(x) => x,
+        );
+    }
+
+    #[test]
+    fn the_shuffle_only_depends_on_the_secrets_and_the_block() {
+        let first = deal_heads_up();
+        let second = deal_heads_up();
+        assert_eq!(
+            load_deck(&first.storage, DEFAULT_TABLE_ID).unwrap(),
+            load_deck(&second.storage, DEFAULT_TABLE_ID).unwrap()
+        );
+    }
+
+    #[test]
+    fn players_act_in_turn() {
+        let mut deps = deal_heads_up();
+
+        assert_error(
+            handle(&mut deps, mock_env("bob", &[]), HandleMsg::Check {}),
+            ContractError::NotYourTurn,
+        );
+        assert_error(
+            handle(&mut deps, mock_env("carol", &[]), HandleMsg::Call {}),
+            ContractError::NotAPlayer,
+        );
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), HandleMsg::Check {}),
+            ContractError::CannotCheck,
+        );
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &[]),
+                HandleMsg::Raise { amount: Uint128(5) },
+            ),
+            ContractError::BelowMinRaise { min: BIG_BLIND },
+        );
+
+        // alice completes the small blind and bob has the option
+        act(&mut deps, "alice", HandleMsg::Call {});
+        assert_eq!(table(&deps).turn, Some(HumanAddr::from("bob")));
+        assert_error(
+            handle(&mut deps, mock_env("bob", &[]), HandleMsg::Call {}),
+            ContractError::NothingToCall,
+        );
+
+        let response = act(
+            &mut deps,
+            "bob",
+            HandleMsg::Raise {
+                amount: Uint128(20),
+            },
+        );
+        assert_eq!(
+            response.log[0].value,
+            r#"{"table":1,"hand":1,"event":{"raise":{"seat":1,"amount":"20","bet":"30","all_in":false}}}"#
+        );
+        assert_eq!(wallet(&deps, "bob"), 970);
+        assert!(table(&deps).stage == Stage::PreFlop);
+
+        act(&mut deps, "alice", HandleMsg::Call {});
+        let table = table(&deps);
+        assert!(table.stage == Stage::Flop);
+        assert_eq!(table.community_cards.len(), 3);
+        assert_eq!(table.pot().unwrap(), Uint128(60));
+        // after the flop the big blind acts first heads-up
+        assert_eq!(table.turn, Some(HumanAddr::from("bob")));
+    }
+
+    #[test]
+    fn short_all_in_does_not_reopen_the_betting() {
+        // alice has the button, bob posts the small blind and carol the big blind
+        let mut deps = deal(&[("alice", BUY_IN), ("bob", BUY_IN), ("carol", 450)]);
+
+        act(
+            &mut deps,
+            "alice",
+            HandleMsg::Raise {
+                amount: Uint128(390),
+            },
+        );
+        act(&mut deps, "bob", HandleMsg::Call {});
+        // carol raises by 50 going all-in, less than the 390 raise she faces
+        act(&mut deps, "carol", HandleMsg::AllIn {});
+        assert_eq!(table(&deps).last_raise, Uint128(390));
+
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &[]),
+                HandleMsg::Raise {
+                    amount: Uint128(390),
+                },
+            ),
+            ContractError::BettingNotReopened,
+        );
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), HandleMsg::AllIn {}),
+            ContractError::BettingNotReopened,
+        );
+        act(&mut deps, "alice", HandleMsg::Call {});
+        act(&mut deps, "bob", HandleMsg::Call {});
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Flop);
+        assert_eq!(table.pot().unwrap(), Uint128(1350));
+    }
+
+    #[test]
+    fn all_in_is_capped_at_the_pot_on_pot_limit_tables() {
+        let players = [("alice", BUY_IN), ("bob", BUY_IN)];
+        let mut deps = deal_at(open_room(Some(BettingStructure::PotLimit), None), &players);
+
+        // 5 to call and a pot of 20 after calling
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        assert_eq!(wallet(&deps, "alice"), 970);
+        // 20 to call and a pot of 60 after calling
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+        assert_eq!(wallet(&deps, "bob"), 910);
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::PreFlop);
+        assert_eq!(table.pot().unwrap(), Uint128(120));
+        assert_eq!(table.turn, Some(HumanAddr::from("alice")));
+    }
+
+    #[test]
+    fn all_in_is_capped_at_the_bet_size_on_fixed_limit_tables() {
+        let players = [("alice", BUY_IN), ("bob", BUY_IN)];
+        let mut deps = deal_at(
+            open_room(Some(BettingStructure::FixedLimit), None),
+            &players,
+        );
+
+        // raise, re-raise and cap, then the last all-in can only call
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        assert_eq!(table(&deps).street_bets, FIXED_LIMIT_MAX_BETS);
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Flop);
+        assert_eq!(table.pot().unwrap(), Uint128(80));
+        assert_eq!(table.players[0].wallet, Uint128(960));
+        assert_eq!(table.players[1].wallet, Uint128(960));
+    }
+
+    #[test]
+    fn fold_gives_the_pot_to_the_last_player_standing() {
+        let mut deps = deal_heads_up();
+
+        act(&mut deps, "alice", HandleMsg::Fold {});
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        assert_eq!(table.winners, vec![HumanAddr::from("bob")]);
+        assert_eq!(table.players[0].wallet, Uint128(995));
+        assert_eq!(table.players[1].wallet, Uint128(1005));
+        assert_eq!(table.players[1].hand, vec![]);
+    }
+
+    #[test]
+    fn best_hand_wins_at_showdown() {
+        let mut deps = deal_heads_up();
+        aces_against_kings(&mut deps);
+
+        act(&mut deps, "alice", HandleMsg::Call {});
+        act(&mut deps, "bob", HandleMsg::Check {});
+        for _street in &["flop", "turn", "river"] {
+            act(&mut deps, "bob", HandleMsg::Check {});
+            act(&mut deps, "alice", HandleMsg::Check {});
+        }
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        assert_eq!(table.winners, vec![HumanAddr::from("alice")]);
+        assert_eq!(table.community_cards.len(), 5);
+        assert_eq!(table.players[0].wallet, Uint128(1010));
+        assert_eq!(table.players[1].wallet, Uint128(990));
+        assert_eq!(
+            table.players[1].hand,
+            vec![
+                card(Value::King, Suit::Spade),
+                card(Value::King, Suit::Heart)
+            ]
+        );
+
+        let history: HandHistoryPage = serde_json::from_slice(
+            &query(
+                &deps,
+                QueryMsg::GetHandHistory {
+                    table_id: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(history.hands.len(), 1);
+        assert_eq!(history.hands[0].winners, vec![HumanAddr::from("alice")]);
+        assert_eq!(history.hands[0].pots[0].amount, Uint128(20));
+    }
+
+    #[test]
+    fn showdown_exports_to_pokerstars() {
+        let mut deps = deal_heads_up();
+        aces_against_kings(&mut deps);
+
+        act(&mut deps, "alice", HandleMsg::Call {});
+        act(&mut deps, "bob", HandleMsg::Check {});
+        for _street in &["flop", "turn", "river"] {
+            act(&mut deps, "bob", HandleMsg::Check {});
+            act(&mut deps, "alice", HandleMsg::Check {});
+        }
+
+        let history = load_history(&deps.storage, DEFAULT_TABLE_ID, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            export_hand(&history),
+            [
+                "PokerStars Hand #10000000001:  Hold'em No Limit (5/10) - 2019/10/23 02:23:39 UTC",
+                "Table 'Secret Hold'em 1' 2-max Seat #1 is the button",
+                "Seat 1: alice (1000 in chips)",
+                "Seat 2: bob (1000 in chips)",
+                "alice: posts small blind 5",
+                "bob: posts big blind 10",
+                "*** HOLE CARDS ***",
+                "alice: calls 5",
+                "bob: checks",
+                "*** FLOP *** [2c 7d 9h]",
+                "bob: checks",
+                "alice: checks",
+                "*** TURN *** [2c 7d 9h] [Js]",
+                "bob: checks",
+                "alice: checks",
+                "*** RIVER *** [2c 7d 9h Js] [3c]",
+                "bob: checks",
+                "alice: checks",
+                "*** SHOW DOWN ***",
+                "alice: shows [As Ah]",
+                "bob: shows [Ks Kh]",
+                "alice collected 20 from pot",
+                "*** SUMMARY ***",
+                "Total pot 20 | Rake 0",
+                "Board [2c 7d 9h Js 3c]",
+                "Seat 1: alice (button) (small blind) showed [As Ah] and won (20)",
+                "Seat 2: bob (big blind) showed [Ks Kh] and lost",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn all_in_runs_out_the_board() {
+        let mut deps = deal_heads_up();
+        aces_against_kings(&mut deps);
+
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        assert_eq!(wallet(&deps, "alice"), 0);
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        assert_eq!(table.community_cards.len(), 5);
+        assert_eq!(table.players[0].wallet, Uint128(2000));
+        assert_eq!(table.players[1].wallet, Uint128(0));
+    }
+
+    #[test]
+    fn side_pots_are_split_with_the_odd_chip_left_of_the_button() {
+        // alice has the button, bob posts the small blind and carol the big blind
+        let mut deps = deal(&[("alice", BUY_IN), ("bob", 800), ("carol", 455)]);
+        // alice and bob play the same ace-king, carol's queen-jack doesn't catch up
+        stack_deck(
+            &mut deps,
+            &[
+                [
+                    card(Value::Ace, Suit::Spade),
+                    card(Value::King, Suit::Diamond),
+                ],
+                [card(Value::Ace, Suit::Heart), card(Value::King, Suit::Club)],
+                [
+                    card(Value::Queen, Suit::Spade),
+                    card(Value::Jack, Suit::Heart),
+                ],
+            ],
+            [
+                card(Value::Two, Suit::Club),
+                card(Value::Seven, Suit::Diamond),
+                card(Value::Nine, Suit::Heart),
+                card(Value::Three, Suit::Spade),
+                card(Value::Eight, Suit::Club),
+            ],
+        );
+
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+        act(&mut deps, "carol", HandleMsg::AllIn {});
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        let players = |names: &[&str]| -> Vec<HumanAddr> {
+            names.iter().map(|name| HumanAddr::from(*name)).collect()
+        };
+        assert_eq!(table.pots.len(), 2);
+        // 455 from each of them, an odd pot
+        assert_eq!(table.pots[0].amount, Uint128(1365));
+        assert_eq!(table.pots[0].eligible, players(&["alice", "bob", "carol"]));
+        assert_eq!(table.pots[0].winners, players(&["bob", "alice"]));
+        // the next 345 from alice and bob, the rest of alice's bet was uncalled
+        assert_eq!(table.pots[1].amount, Uint128(690));
+        assert_eq!(table.pots[1].eligible, players(&["alice", "bob"]));
+        assert_eq!(table.pots[1].winners, players(&["bob", "alice"]));
+
+        // bob sits left of the button and gets the odd chip of the main pot
+        assert_eq!(table.players[0].wallet, Uint128(200 + 682 + 345));
+        assert_eq!(table.players[1].wallet, Uint128(683 + 345));
+        assert_eq!(table.players[2].wallet, Uint128(0));
+    }
+
+    #[test]
+    fn rake_is_taken_from_pots_and_paid_to_the_collector() {
+        // 5% capped at one big blind
+        let mut deps = seat_heads_up(create_raked_room(Some(house_rake(500, Some(1)))));
+        aces_against_kings(&mut deps);
+
+        act(&mut deps, "alice", HandleMsg::Call {});
+        act(&mut deps, "bob", HandleMsg::Check {});
+        for _street in &["flop", "turn", "river"] {
+            act(&mut deps, "bob", HandleMsg::Check {});
+            act(&mut deps, "alice", HandleMsg::Check {});
+        }
+
+        let checked_down = table(&deps);
+        assert_eq!(checked_down.pots[0].amount, Uint128(20));
+        assert_eq!(checked_down.pots[0].rake, Uint128(1));
+        assert_eq!(checked_down.uncollected_rake, Uint128(1));
+        assert_eq!(
+            load_raked_tables(&deps.storage).unwrap(),
+            vec![DEFAULT_TABLE_ID]
+        );
+        assert_eq!(checked_down.players[0].wallet, Uint128(1009));
+        assert_eq!(checked_down.players[1].wallet, Uint128(990));
+
+        assert_error(
+            handle(&mut deps, mock_env("bob", &[]), HandleMsg::CollectRake {}),
+            ContractError::NotRakeCollector,
+        );
+        let response = act(&mut deps, "house", HandleMsg::CollectRake {});
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("house"),
+                amount: coins(1, "uscrt"),
+            })]
+        );
+        assert!(load_raked_tables(&deps.storage).unwrap().is_empty());
+        assert_error(
+            handle(&mut deps, mock_env("house", &[]), HandleMsg::CollectRake {}),
+            ContractError::NothingToCollect,
+        );
+
+        // all in, 5% of the pot would be 100 but the cap is 10
+        let mut deps = seat_heads_up(create_raked_room(Some(house_rake(500, Some(1)))));
+        aces_against_kings(&mut deps);
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+
+        let all_in = table(&deps);
+        assert_eq!(all_in.pots[0].rake, Uint128(10));
+        assert_eq!(all_in.players[0].wallet, Uint128(1990));
+    }
+
+    #[test]
+    fn no_flop_no_drop_and_uncalled_bets_are_not_raked() {
+        let mut deps = seat_heads_up(create_raked_room(Some(house_rake(500, None))));
+        act(&mut deps, "alice", HandleMsg::Fold {});
+
+        let folded_pre_flop = table(&deps);
+        assert_eq!(folded_pre_flop.uncollected_rake, Uint128(0));
+        assert!(load_raked_tables(&deps.storage).unwrap().is_empty());
+        assert_eq!(folded_pre_flop.players[1].wallet, Uint128(1005));
+
+        let mut deps = seat_heads_up(create_raked_room(Some(house_rake(500, None))));
+        act(&mut deps, "alice", HandleMsg::Call {});
+        act(&mut deps, "bob", HandleMsg::Check {});
+        act(
+            &mut deps,
+            "bob",
+            HandleMsg::Raise {
+                amount: Uint128(50),
+            },
+        );
+        act(&mut deps, "alice", HandleMsg::Fold {});
+
+        // bob gets his bet back and the 20 chips pot, less 1 chip of rake
+        let table = table(&deps);
+        assert_eq!(table.pots[0].amount, Uint128(20));
+        assert_eq!(table.uncollected_rake, Uint128(1));
+        assert_eq!(table.players[0].wallet, Uint128(990));
+        assert_eq!(table.players[1].wallet, Uint128(1009));
+    }
+
+    #[test]
+    fn coins_that_are_not_deposited_go_back() {
+        let mut deps = create_room();
+        let env = mock_env(
+            "alice",
+            &[
+                Coin {
+                    denom: String::from("uscrt"),
+                    amount: Uint128(600),
+                },
+                Coin {
+                    denom: String::from("uatom"),
+                    amount: Uint128(7),
+                },
+                Coin {
+                    denom: String::from("uscrt"),
+                    amount: Uint128(400),
+                },
+            ],
+        );
+        let contract = env.contract.address.clone();
+        let join = HandleMsg::Join {
+            commitment: commitment("alice", 1),
+        };
+        let response = handle(&mut deps, env, join).unwrap();
+
+        // every coin of the table's denom is deposited, the others are refunded
+        assert_eq!(wallet(&deps, "alice"), BUY_IN);
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: contract.clone(),
+                to_address: HumanAddr::from("alice"),
+                amount: coins(7, "uatom"),
+            })]
+        );
+
+        // and so is anything sent with a message that doesn't take a deposit
+        let response = handle(
+            &mut deps,
+            mock_env("alice", &coins(50, "uscrt")),
+            HandleMsg::CreateViewingKey {
+                entropy: String::from("entropy"),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: contract,
+                to_address: HumanAddr::from("alice"),
+                amount: coins(50, "uscrt"),
+            })]
+        );
+    }
+
+    #[test]
+    fn withdraw_mid_hand_folds_and_pays_out() {
+        let mut deps = deal_heads_up();
+
+        let env = mock_env("bob", &[]);
+        let contract = env.contract.address.clone();
+        let response = handle(&mut deps, env, HandleMsg::Withdraw {}).unwrap();
+
+        assert_eq!(
+            response.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: contract,
+                to_address: HumanAddr::from("bob"),
+                amount: coins(990, "uscrt"),
+            })]
+        );
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        assert_eq!(table.winners, vec![HumanAddr::from("alice")]);
+        assert_eq!(table.players[0].wallet, Uint128(1010));
+        assert_eq!(table.players[1].wallet, Uint128(0));
+    }
+
+    #[test]
+    fn token_tables_take_deposits_through_receive() {
+        let mut deps = mock_dependencies(20, &[]);
+        let token = HumanAddr::from("token");
+        let msg = InitMsg::CreateRoom {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: Some(Currency::Snip20 {
+                address: token.clone(),
+                code_hash: String::from("token code hash"),
+            }),
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        let env = mock_env("creator", &[]);
+        let register =
+            register_receive_msg(&token, "token code hash", env.contract_code_hash.clone());
+        let response = init(&mut deps, env, msg).unwrap();
+        assert_eq!(response.messages, vec![register.unwrap()]);
+
+        let join = HandleMsg::Join {
+            commitment: commitment("alice", 1),
+        };
+        let wrong_token = ContractError::WrongDenom {
+            expected: String::from("token"),
+        };
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(BUY_IN, "uscrt")),
+                join.clone(),
+            ),
+            wrong_token.clone(),
+        );
+
+        // only the token itself can tell the table that tokens were sent
+        let receive = HandleMsg::Receive {
+            sender: HumanAddr::from("alice"),
+            from: HumanAddr::from("alice"),
+            amount: Uint128(BUY_IN),
+            msg: Some(to_binary(&join).unwrap()),
+        };
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), receive.clone()),
+            wrong_token,
+        );
+        handle(&mut deps, mock_env("token", &[]), receive).unwrap();
+        assert_eq!(wallet(&deps, "alice"), BUY_IN);
+
+        let response = act(&mut deps, "alice", HandleMsg::Withdraw {});
+        let transfer = transfer_msg(
+            &token,
+            "token code hash",
+            HumanAddr::from("alice"),
+            Uint128(BUY_IN),
+        );
+        assert_eq!(response.messages, vec![transfer.unwrap()]);
+    }
+
+    #[test]
+    fn rematch_deals_a_new_hand_with_fresh_secrets() {
+        let mut deps = deal_heads_up();
+        act(&mut deps, "alice", HandleMsg::Fold {});
+
+        let reused = HandleMsg::Rematch {
+            commitment: commitment("alice", 1),
+        };
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), reused),
+            ContractError::ReusedCommitment,
+        );
+
+        for player in &["alice", "bob"] {
+            let msg = HandleMsg::Rematch {
+                commitment: commitment(player, 2),
+            };
+            act(&mut deps, player, msg);
+        }
+        assert!(table(&deps).stage == Stage::WaitingForSecrets);
+
+        for player in &["alice", "bob"] {
+            let msg = HandleMsg::Reveal {
+                secret: secret(player, 2),
+            };
+            act(&mut deps, player, msg);
+        }
+
+        // the button moved to bob, who posts the small blind
+        let table = table(&deps);
+        assert!(table.stage == Stage::PreFlop);
+        assert_eq!(table.game_counter, 2);
+        assert_eq!(table.button, Some(1));
+        assert_eq!(table.turn, Some(HumanAddr::from("bob")));
+        assert_eq!(table.players[0].wallet, Uint128(985));
+        assert_eq!(table.players[1].wallet, Uint128(1000));
+    }
+
+    #[test]
+    fn busted_players_cannot_ask_for_a_rematch() {
+        let mut deps = deal_heads_up();
+        aces_against_kings(&mut deps);
+        act(&mut deps, "alice", HandleMsg::AllIn {});
+        act(&mut deps, "bob", HandleMsg::AllIn {});
+        assert_eq!(wallet(&deps, "bob"), 0);
+
+        let rematch = |player: &str| HandleMsg::Rematch {
+            commitment: commitment(player, 2),
+        };
+        assert_error(
+            handle(&mut deps, mock_env("bob", &[]), rematch("bob")),
+            ContractError::CannotCoverBlinds,
+        );
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), rematch("alice")),
+            ContractError::NotEnoughPlayers,
+        );
+    }
+
+    #[test]
+    fn the_button_moves_on_from_the_seat_of_a_player_who_left() {
+        let mut deps = deal(&[("alice", BUY_IN), ("bob", BUY_IN), ("carol", BUY_IN)]);
+        act(&mut deps, "alice", HandleMsg::Fold {});
+        act(&mut deps, "bob", HandleMsg::Fold {});
+
+        // alice had the button, dave takes her seat before the next hand
+        act(&mut deps, "alice", HandleMsg::Leave {});
+        let msg = HandleMsg::Join {
+            commitment: commitment("dave", 1),
+        };
+        handle(&mut deps, mock_env("dave", &coins(BUY_IN, "uscrt")), msg).unwrap();
+        for player in &["bob", "carol"] {
+            let msg = HandleMsg::Rematch {
+                commitment: commitment(player, 2),
+            };
+            act(&mut deps, player, msg);
+        }
+        for (player, hand) in &[("dave", 1), ("bob", 2), ("carol", 2)] {
+            let msg = HandleMsg::Reveal {
+                secret: secret(player, *hand),
+            };
+            act(&mut deps, player, msg);
+        }
+
+        let table = table(&deps);
+        assert!(table.stage == Stage::PreFlop);
+        assert_eq!(table.players[0].address, HumanAddr::from("dave"));
+        assert_eq!(table.button, Some(1));
+        // carol posts the small blind with the one she won, dave the big blind and bob acts first
+        assert_eq!(table.players[2].wallet, Uint128(BUY_IN));
+        assert_eq!(table.players[0].wallet, Uint128(BUY_IN - BIG_BLIND));
+        assert_eq!(table.turn, Some(HumanAddr::from("bob")));
+    }
+
+    #[test]
+    fn hole_cards_stay_the_same_when_a_player_leaves_mid_hand() {
+        let mut deps = deal(&[("alice", BUY_IN), ("bob", BUY_IN), ("carol", BUY_IN)]);
+        act(&mut deps, "alice", HandleMsg::Fold {});
+        act(&mut deps, "bob", HandleMsg::Fold {});
+
+        // alice sits the next hand out and leaves while bob and carol play it
+        for player in &["bob", "carol"] {
+            let msg = HandleMsg::Rematch {
+                commitment: commitment(player, 2),
             };
+            act(&mut deps, player, msg);
+        }
+        let mut env = mock_env("bob", &[]);
+        env.block.height += DEFAULT_ACTION_TIMEOUT;
+        handle(&mut deps, env, HandleMsg::ClaimTimeout {}).unwrap();
+        for player in &["bob", "carol"] {
+            let msg = HandleMsg::Reveal {
+                secret: secret(player, 2),
+            };
+            act(&mut deps, player, msg);
+        }
+        assert!(table(&deps).stage == Stage::PreFlop);
+
+        let key = String::from("carol's key");
+        act(
+            &mut deps,
+            "carol",
+            HandleMsg::SetViewingKey { key: key.clone() },
+        );
+        let my_hand = QueryMsg::GetMyHand {
+            address: HumanAddr::from("carol"),
+            key,
+        };
+        let before = query(&deps, my_hand.clone()).unwrap();
+        act(&mut deps, "alice", HandleMsg::Leave {});
+        assert_eq!(query(&deps, my_hand).unwrap(), before);
+    }
+
+    #[test]
+    fn absent_players_can_be_timed_out() {
+        let mut deps = deal_heads_up();
+
+        assert_error(
+            handle(&mut deps, mock_env("bob", &[]), HandleMsg::ClaimTimeout {}),
+            ContractError::TimeoutNotReached {
+                blocks_left: DEFAULT_ACTION_TIMEOUT,
+            },
+        );
+
+        let mut env = mock_env("bob", &[]);
+        env.block.height += DEFAULT_ACTION_TIMEOUT;
+        handle(&mut deps, env, HandleMsg::ClaimTimeout {}).unwrap();
+
+        // alice couldn't check facing the big blind
+        let table = table(&deps);
+        assert!(table.stage == Stage::Ended);
+        assert_eq!(table.winners, vec![HumanAddr::from("bob")]);
+    }
+
+    #[test]
+    fn players_who_dont_approve_the_next_hand_sit_out() {
+        let mut deps = deal(&[("alice", BUY_IN), ("bob", BUY_IN), ("carol", BUY_IN)]);
+        act(&mut deps, "alice", HandleMsg::Fold {});
+        act(&mut deps, "bob", HandleMsg::Fold {});
+
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &[]),
+                HandleMsg::ClaimTimeout {},
+            ),
+            ContractError::RematchBeforeClaiming,
+        );
+        for player in &["alice", "carol"] {
+            let msg = HandleMsg::Rematch {
+                commitment: commitment(player, 2),
+            };
+            act(&mut deps, player, msg);
+        }
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &[]),
+                HandleMsg::ClaimTimeout {},
+            ),
+            ContractError::TimeoutNotReached {
+                blocks_left: DEFAULT_ACTION_TIMEOUT,
+            },
+        );
+
+        // bob never sends Rematch, the next hand is dealt without him
+        let mut env = mock_env("alice", &[]);
+        env.block.height += DEFAULT_ACTION_TIMEOUT;
+        handle(&mut deps, env, HandleMsg::ClaimTimeout {}).unwrap();
+        let table = table(&deps);
+        assert!(table.stage == Stage::WaitingForSecrets);
+        assert!(table.players[0].in_hand && table.players[2].in_hand);
+        assert!(!table.players[1].in_hand);
+        assert!(table.players[1].sitting_out);
+    }
+
+    #[test]
+    fn joining_after_a_cancelled_first_hand_waits_for_rematches() {
+        let mut deps = open_room(None, Some(3));
+        for player in &["alice", "bob"] {
+            let msg = HandleMsg::Join {
+                commitment: commitment(player, 1),
+            };
+            handle(&mut deps, mock_env(*player, &coins(BUY_IN, "uscrt")), msg).unwrap();
+        }
+        let msg = HandleMsg::Reveal {
+            secret: secret("alice", 1),
+        };
+        act(&mut deps, "alice", msg);
+
+        // bob never reveals, the hand is cancelled and he cashes out
+        let mut env = mock_env("alice", &[]);
+        env.block.height += DEFAULT_ACTION_TIMEOUT;
+        handle(&mut deps, env, HandleMsg::ClaimTimeout {}).unwrap();
+        assert!(table(&deps).stage == Stage::WaitingForPlayersToJoin);
+        act(&mut deps, "bob", HandleMsg::Withdraw {});
+
+        let msg = HandleMsg::Join {
+            commitment: commitment("carol", 1),
+        };
+        handle(&mut deps, mock_env("carol", &coins(BUY_IN, "uscrt")), msg).unwrap();
+        assert!(table(&deps).stage == Stage::WaitingForPlayersToJoin);
+
+        let msg = HandleMsg::Rematch {
+            commitment: commitment("alice", 2),
+        };
+        act(&mut deps, "alice", msg);
+        let table = table(&deps);
+        assert!(table.stage == Stage::WaitingForSecrets);
+        assert!(table.players[0].in_hand && table.players[2].in_hand);
+        assert!(!table.players[1].in_hand);
+    }
+
+    #[test]
+    fn viewing_keys_depend_on_the_room_seed() {
+        let create_key = |prng_seed: &[u8]| {
+            let mut deps = mock_dependencies(20, &[]);
+            let msg = InitMsg::CreateRoom {
+                big_blind: Uint128(BIG_BLIND),
+                betting_structure: None,
+                max_seats: None,
+                action_timeout: None,
+                min_buy_in: None,
+                max_buy_in: None,
+                currency: None,
+                rake: None,
+                prng_seed: Binary(prng_seed.to_vec()),
+            };
+            init(&mut deps, mock_env("creator", &[]), msg).unwrap();
+            let msg = HandleMsg::CreateViewingKey {
+                entropy: String::from("entropy"),
+            };
+            act(&mut deps, "alice", msg).data.unwrap()
+        };
+
+        assert_ne!(create_key(b"one seed"), create_key(b"another seed"));
+    }
+
+    fn list_tables(deps: &Deps, filter: Option<TableFilter>) -> Vec<u64> {
+        let list: TableList = serde_json::from_slice(
+            &query(
+                deps,
+                QueryMsg::ListTables {
+                    filter,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        list.tables.iter().map(|table| table.table_id).collect()
+    }
+
+    #[test]
+    fn a_lobby_hosts_many_tables() {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg::CreateLobby {
+            rake: None,
+            prng_seed: Binary(b"seed".to_vec()),
+        };
+        init(&mut deps, mock_env("creator", &[]), msg).unwrap();
+        assert_eq!(list_tables(&deps, None), Vec::<u64>::new());
+
+        for big_blind in &[10, 50] {
+            let msg = HandleMsg::CreateTable {
+                big_blind: Uint128(*big_blind),
+                betting_structure: None,
+                max_seats: Some(3),
+                action_timeout: None,
+                min_buy_in: None,
+                max_buy_in: None,
+                currency: None,
+            };
+            act(&mut deps, "creator", msg);
+        }
+        let big_blinds = TableFilter {
+            big_blind: Some(Uint128(50)),
+            ..TableFilter::default()
+        };
+        assert_eq!(list_tables(&deps, None), vec![1, 2]);
+        assert_eq!(list_tables(&deps, Some(big_blinds)), vec![2]);
+
+        let join = |table_id: u64| HandleMsg::JoinTable {
+            table_id,
+            commitment: commitment("alice", 1),
+        };
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(BUY_IN, "uscrt")),
+                join(3),
+            ),
+            ContractError::TableNotFound,
+        );
+        handle(
+            &mut deps,
+            mock_env("alice", &coins(BUY_IN, "uscrt")),
+            join(1),
+        )
+        .unwrap();
+        assert_error(
+            handle(
+                &mut deps,
+                mock_env("alice", &coins(BUY_IN, "uscrt")),
+                join(2),
+            ),
+            ContractError::AlreadySeated,
+        );
+
+        // leaving pays out the wallet and frees the player to sit somewhere else
+        let response = act(&mut deps, "alice", HandleMsg::Leave {});
+        assert_eq!(paid_out(&response), BUY_IN);
+        assert_eq!(table(&deps).players.len(), 0);
+        let join = HandleMsg::JoinTable {
+            table_id: 2,
+            commitment: commitment("alice", 2),
+        };
+        handle(&mut deps, mock_env("alice", &coins(5000, "uscrt")), join).unwrap();
+
+        let full = TableFilter {
+            free_seat: Some(false),
+            ..TableFilter::default()
+        };
+        assert_eq!(list_tables(&deps, Some(full)), Vec::<u64>::new());
+        let public_data = query(&deps, QueryMsg::GetPublicData { table_id: Some(2) }).unwrap();
+        let table_2: Table = serde_json::from_slice(&public_data).unwrap();
+        assert_eq!(table_2.players[0].address, HumanAddr::from("alice"));
+    }
+
+    #[test]
+    fn a_room_is_a_closed_lobby() {
+        let mut deps = create_room();
+        let msg = HandleMsg::CreateTable {
+            big_blind: Uint128(BIG_BLIND),
+            betting_structure: None,
+            max_seats: None,
+            action_timeout: None,
+            min_buy_in: None,
+            max_buy_in: None,
+            currency: None,
+        };
+        assert_error(
+            handle(&mut deps, mock_env("alice", &[]), msg),
+            ContractError::NotALobby,
+        );
+        assert_eq!(list_tables(&deps, None), vec![DEFAULT_TABLE_ID]);
+    }
 
-            let first_card_index;
-            let second_card_index;
-            if secret_bytes == player_a_secret {
-                first_card_index = PLAYER_A_FIRST_CARD;
-                second_card_index = PLAYER_A_SECOND_CARD;
-            } else if secret_bytes == player_b_secret {
-                first_card_index = PLAYER_B_FIRST_CARD;
-                second_card_index = PLAYER_B_SECOND_CARD;
+    const PLAYERS: [&str; 5] = ["alice", "bob", "carol", "dave", "eve"];
+
+    /// MockStorage can't be copied, this one can so a failed message can be rolled back.
+    #[derive(Clone, Default)]
+    struct SnapshotStorage(BTreeMap<Vec<u8>, Vec<u8>>);
+
+    impl ReadonlyStorage for SnapshotStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.0.get(key).cloned()
+        }
+    }
+
+    impl Storage for SnapshotStorage {
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.0.insert(key.to_vec(), value.to_vec());
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.0.remove(key);
+        }
+    }
+
+    type SnapshotDeps = Extern<SnapshotStorage, MockApi, MockQuerier>;
+
+    #[derive(Clone, Debug)]
+    enum RaiseSize {
+        BigBlind,
+        TwoBigBlinds,
+        Wallet,
+        // a share of the wallet in percent, past 100 it's more than the player has
+        Share(u64),
+    }
+
+    /// What a player tries to do, legal or not. Amounts are resolved against the table
+    /// when the action is played.
+    #[derive(Clone, Debug)]
+    enum Action {
+        Check,
+        Call,
+        Fold,
+        Raise(RaiseSize),
+        AllIn,
+        Withdraw,
+        Leave,
+        CollectRake,
+        TopUp { amount: u128, wrong_denom: bool },
+        ClaimTimeout,
+        Join { amount: u128, wrong_denom: bool },
+        Rematch,
+        // now and then with a secret that doesn't match the commitment
+        Reveal { wrong_secret: bool },
+    }
+
+    #[derive(Clone, Debug)]
+    struct Step {
+        player: usize,
+        action: Action,
+        blocks: u64,                      // blocks since the previous step
+        stray_coin: Option<(bool, u128)>, // sent along with the message, true for another denom
+    }
+
+    fn deposit_strategy() -> impl Strategy<Value = (u128, bool)> {
+        (0..1200u128, prop::bool::weighted(0.1))
+    }
+
+    fn action_strategy() -> impl Strategy<Value = Action> {
+        let raise_size = prop_oneof![
+            Just(RaiseSize::BigBlind),
+            Just(RaiseSize::TwoBigBlinds),
+            Just(RaiseSize::Wallet),
+            (0..110u64).prop_map(RaiseSize::Share),
+        ];
+        prop_oneof![
+            3 => Just(Action::Check),
+            3 => Just(Action::Call),
+            2 => Just(Action::Fold),
+            3 => raise_size.prop_map(Action::Raise),
+            1 => Just(Action::AllIn),
+            1 => Just(Action::Withdraw),
+            1 => Just(Action::Leave),
+            1 => deposit_strategy()
+                .prop_map(|(amount, wrong_denom)| Action::TopUp { amount, wrong_denom }),
+            1 => Just(Action::ClaimTimeout),
+            1 => Just(Action::CollectRake),
+            1 => deposit_strategy()
+                .prop_map(|(amount, wrong_denom)| Action::Join { amount, wrong_denom }),
+            1 => Just(Action::Rematch),
+            3 => prop::bool::weighted(0.1).prop_map(|wrong_secret| Action::Reveal { wrong_secret }),
+        ]
+    }
+
+    fn steps_strategy() -> impl Strategy<Value = Vec<Step>> {
+        prop::collection::vec(
+            (
+                0..PLAYERS.len(),
+                action_strategy(),
+                0..10u64,
+                prop::option::weighted(0.1, (prop::bool::ANY, 0..100u128)),
+            )
+                .prop_map(|(player, action, blocks, stray_coin)| Step {
+                    player,
+                    action,
+                    blocks,
+                    stray_coin,
+                }),
+            1..400,
+        )
+    }
+
+    /// Room settings, kept apart from InitMsg so proptest can print the failing case.
+    #[derive(Clone, Debug)]
+    struct Room {
+        big_blind: u128,
+        pot_limit: bool,
+        fixed_limit: bool,
+        max_seats: u8,
+        action_timeout: u64,
+        min_buy_in: u32,
+        max_buy_in: u32,
+        rake: Option<(u16, Option<u32>, bool)>, // basis points, cap and no flop no drop
+    }
+
+    impl Room {
+        fn init_msg(&self) -> InitMsg {
+            let betting_structure = if self.fixed_limit {
+                BettingStructure::FixedLimit
+            } else if self.pot_limit {
+                BettingStructure::PotLimit
             } else {
-                return Err(StdError::generic_err("You are not a player, go away!"));
+                BettingStructure::NoLimit
+            };
+            InitMsg::CreateRoom {
+                big_blind: Uint128(self.big_blind),
+                betting_structure: Some(betting_structure),
+                max_seats: Some(self.max_seats),
+                action_timeout: Some(self.action_timeout),
+                min_buy_in: Some(self.min_buy_in),
+                max_buy_in: Some(self.max_buy_in),
+                currency: None,
+                // the first player collects it
+                rake: self.rake.map(|(basis_points, cap, no_flop_no_drop)| Rake {
+                    basis_points,
+                    cap,
+                    no_flop_no_drop,
+                    collector: HumanAddr::from(PLAYERS[0]),
+                }),
+                prng_seed: Binary(b"seed".to_vec()),
             }
+        }
+    }
 
-            let deck: Vec<Card> =
-                serde_json::from_slice(&deps.storage.get(b"deck").unwrap()).unwrap();
+    fn room_strategy() -> impl Strategy<Value = Room> {
+        (
+            1..10u128,
+            prop::bool::ANY,
+            prop::bool::weighted(0.33),
+            MIN_SEATS..5,
+            1..20u64,
+            1..50u32,
+            0..200u32,
+            prop::option::of((
+                0..=MAX_RAKE_BASIS_POINTS,
+                prop::option::of(0..5u32),
+                prop::bool::ANY,
+            )),
+        )
+            .prop_map(
+                |(
+                    half_big_blind,
+                    pot_limit,
+                    fixed_limit,
+                    max_seats,
+                    action_timeout,
+                    min_buy_in,
+                    extra_buy_in,
+                    rake,
+                )| Room {
+                    big_blind: half_big_blind * 2, // big blinds are even
+                    pot_limit,
+                    fixed_limit,
+                    max_seats,
+                    action_timeout,
+                    min_buy_in,
+                    max_buy_in: min_buy_in + extra_buy_in,
+                    rake,
+                },
+            )
+    }
 
-            let first_card: Card = deck[first_card_index];
-            let second_card: Card = deck[second_card_index];
+    /// The message `action` stands for at the current table, and the funds sent with it.
+    /// `commitments` counts the secrets each player committed to so far.
+    fn message(
+        table: &Table,
+        player: &str,
+        action: &Action,
+        commitments: &HashMap<&str, u64>,
+    ) -> (HandleMsg, Vec<Coin>) {
+        let wallet = match table.seat_of(&HumanAddr::from(player)) {
+            Some(seat) => table.players[seat].wallet.u128(),
+            None => 0,
+        };
+        let committed = commitments.get(player).cloned().unwrap_or(0);
+        let deposit = |amount: u128, wrong_denom: bool| {
+            coins(amount, if wrong_denom { "uatom" } else { "uscrt" })
+        };
+
+        match action {
+            Action::Check => (HandleMsg::Check {}, vec![]),
+            Action::Call => (HandleMsg::Call {}, vec![]),
+            Action::Fold => (HandleMsg::Fold {}, vec![]),
+            Action::Raise(size) => {
+                let amount = match size {
+                    RaiseSize::BigBlind => table.big_blind.u128(),
+                    RaiseSize::TwoBigBlinds => table.big_blind.u128() * 2,
+                    RaiseSize::Wallet => wallet,
+                    RaiseSize::Share(percent) => wallet * *percent as u128 / 100,
+                };
+                let amount = Uint128(amount);
+                (HandleMsg::Raise { amount }, vec![])
+            }
+            Action::AllIn => (HandleMsg::AllIn {}, vec![]),
+            Action::Withdraw => (HandleMsg::Withdraw {}, vec![]),
+            Action::Leave => (HandleMsg::Leave {}, vec![]),
+            Action::TopUp {
+                amount,
+                wrong_denom,
+            } => (HandleMsg::TopUp {}, deposit(*amount, *wrong_denom)),
+            Action::ClaimTimeout => (HandleMsg::ClaimTimeout {}, vec![]),
+            Action::CollectRake => (HandleMsg::CollectRake {}, vec![]),
+            Action::Join {
+                amount,
+                wrong_denom,
+            } => (
+                HandleMsg::Join {
+                    commitment: commitment(player, committed + 1),
+                },
+                deposit(*amount, *wrong_denom),
+            ),
+            Action::Rematch => (
+                HandleMsg::Rematch {
+                    commitment: commitment(player, committed + 1),
+                },
+                vec![],
+            ),
+            Action::Reveal { wrong_secret } => {
+                let hand = if *wrong_secret {
+                    committed + 1
+                } else {
+                    committed
+                };
+                (
+                    HandleMsg::Reveal {
+                        secret: secret(player, hand),
+                    },
+                    vec![],
+                )
+            }
+        }
+    }
 
-            return Ok(Binary(
-                serde_json::to_vec(&vec![first_card, second_card]).unwrap(),
-            ));
+    fn paid_out(response: &HandleResponse) -> u128 {
+        response
+            .messages
+            .iter()
+            .map(|msg| match msg {
+                CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                    amount.iter().map(|coin| coin.amount.u128()).sum()
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Plays `steps` at a room created with `room` and checks after each one that every chip
+    /// deposited is either on the table or paid out. A failed message is rolled back along with
+    /// the funds sent with it, like the chain does.
+    fn play_table(room: &Room, steps: &[Step]) -> Result<(), TestCaseError> {
+        let mock = mock_dependencies(20, &[]);
+        let mut deps: SnapshotDeps = Extern {
+            storage: SnapshotStorage::default(),
+            api: mock.api,
+            querier: mock.querier,
+        };
+        init(&mut deps, mock_env("creator", &[]), room.init_msg()).unwrap();
+
+        let mut height = 12_345;
+        let mut deposited: u128 = 0;
+        let mut withdrawn: u128 = 0;
+        let mut commitments: HashMap<&str, u64> = HashMap::new();
+
+        for (i, step) in steps.iter().enumerate() {
+            let player = PLAYERS[step.player];
+            let (msg, mut funds) = message(&table(&deps), player, &step.action, &commitments);
+            let commits = matches!(msg, HandleMsg::Join { .. } | HandleMsg::Rematch { .. });
+            // now and then send a stray coin along, deposit or not
+            if let Some((other_denom, amount)) = step.stray_coin {
+                let denom = if other_denom { "uatom" } else { "uscrt" };
+                funds.push(Coin {
+                    denom: String::from(denom),
+                    amount: Uint128(amount),
+                });
+            }
+
+            height += step.blocks;
+            let mut env = mock_env(player, &funds);
+            env.block.height = height;
+
+            let snapshot = deps.storage.clone();
+            match handle(&mut deps, env, msg) {
+                Ok(response) => {
+                    deposited += funds.iter().map(|coin| coin.amount.u128()).sum::<u128>();
+                    withdrawn += paid_out(&response);
+                    if commits {
+                        *commitments.entry(player).or_insert(0) += 1;
+                    }
+                }
+                Err(_) => deps.storage = snapshot,
+            }
+
+            let table = table(&deps);
+            let stacks: u128 = table
+                .players
+                .iter()
+                .map(|p| p.wallet.u128() + p.committed.u128())
+                .sum();
+            let on_table = stacks + table.uncollected_rake.u128();
+            prop_assert_eq!(
+                on_table as i128,
+                deposited as i128 - withdrawn as i128,
+                "step {}",
+                i
+            );
+            for p in table.players.iter() {
+                prop_assert!(p.bet <= p.committed, "step {}", i);
+            }
+            if table.stage.no_more_action() {
+                prop_assert_eq!(
+                    table.pot().unwrap(),
+                    Uint128::zero(),
+                    "step {}: chips left in the pot",
+                    i
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        fn chips_are_never_created_or_lost(room in room_strategy(), steps in steps_strategy()) {
+            play_table(&room, &steps)?;
         }
     }
 }
@@ -0,0 +1,289 @@
+use cosmwasm_std::StdError;
+use serde::Serialize;
+use serde_json_wasm as serde_json;
+use std::fmt;
+
+/// Every way a message to the contract can fail. Errors reach clients as a `generic_err`
+/// holding `{"code":"...","message":"..."}`, where `code` is the stable snake_case name
+/// of the variant and `message` is for humans.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractError {
+    // room creation
+    InvalidBigBlind,
+    InvalidSeats { min: u8, max: u8 },
+    InvalidActionTimeout,
+    InvalidBuyIn,
+    InvalidRake { max: u16 },
+
+    // lobby
+    NotALobby,
+    TableNotFound,
+
+    // deposits and withdrawals
+    NoFunds,
+    WrongDenom { expected: String },
+    BelowMinBuyIn { min: u128 },
+    AboveMaxBuyIn { max: u128 },
+    NothingToWithdraw,
+    InvalidReceiveMsg,
+
+    // rake
+    NotRakeCollector,
+    NothingToCollect,
+
+    // seating
+    NotAPlayer,
+    AlreadySeated,
+    TableFull,
+    NotEnoughPlayers,
+    CannotCoverBlinds,
+
+    // hand flow
+    HandNotStarted,
+    HandInProgress,
+    NotYourTurn,
+    NotDealtIn,
+
+    // shuffle
+    InvalidCommitment,
+    ReusedCommitment,
+    WaitingForSecrets,
+    NothingToReveal,
+    AlreadyRevealed,
+    SecretTooShort { min: usize },
+    SecretMismatch,
+
+    // betting
+    CannotCheck,
+    NothingToCall,
+    ZeroRaise,
+    BelowMinRaise { min: u128 },
+    AboveMaxRaise { max: u128 },
+    FixedLimitRaise { size: u128 },
+    BettingCapped,
+    EveryoneAllIn,
+    BettingNotReopened,
+    InsufficientFunds,
+    Overflow,
+
+    // timeouts
+    CannotTimeOutYourself,
+    RevealBeforeClaiming,
+    RematchBeforeClaiming,
+    TimeoutNotReached { blocks_left: u64 },
+
+    // queries
+    WrongViewingKey,
+    HandProofNotAvailable,
+    HandNotFound,
+}
+
+impl ContractError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBigBlind => "invalid_big_blind",
+            Self::InvalidSeats { .. } => "invalid_seats",
+            Self::InvalidActionTimeout => "invalid_action_timeout",
+            Self::InvalidBuyIn => "invalid_buy_in",
+            Self::InvalidRake { .. } => "invalid_rake",
+            Self::NotALobby => "not_a_lobby",
+            Self::TableNotFound => "table_not_found",
+            Self::NoFunds => "no_funds",
+            Self::WrongDenom { .. } => "wrong_denom",
+            Self::BelowMinBuyIn { .. } => "below_min_buy_in",
+            Self::AboveMaxBuyIn { .. } => "above_max_buy_in",
+            Self::NothingToWithdraw => "nothing_to_withdraw",
+            Self::InvalidReceiveMsg => "invalid_receive_msg",
+            Self::NotRakeCollector => "not_rake_collector",
+            Self::NothingToCollect => "nothing_to_collect",
+            Self::NotAPlayer => "not_a_player",
+            Self::AlreadySeated => "already_seated",
+            Self::TableFull => "table_full",
+            Self::NotEnoughPlayers => "not_enough_players",
+            Self::CannotCoverBlinds => "cannot_cover_blinds",
+            Self::HandNotStarted => "hand_not_started",
+            Self::HandInProgress => "hand_in_progress",
+            Self::NotYourTurn => "not_your_turn",
+            Self::NotDealtIn => "not_dealt_in",
+            Self::InvalidCommitment => "invalid_commitment",
+            Self::ReusedCommitment => "reused_commitment",
+            Self::WaitingForSecrets => "waiting_for_secrets",
+            Self::NothingToReveal => "nothing_to_reveal",
+            Self::AlreadyRevealed => "already_revealed",
+            Self::SecretTooShort { .. } => "secret_too_short",
+            Self::SecretMismatch => "secret_mismatch",
+            Self::CannotCheck => "cannot_check",
+            Self::NothingToCall => "nothing_to_call",
+            Self::ZeroRaise => "zero_raise",
+            Self::BelowMinRaise { .. } => "below_min_raise",
+            Self::AboveMaxRaise { .. } => "above_max_raise",
+            Self::FixedLimitRaise { .. } => "fixed_limit_raise",
+            Self::BettingCapped => "betting_capped",
+            Self::EveryoneAllIn => "everyone_all_in",
+            Self::BettingNotReopened => "betting_not_reopened",
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::Overflow => "overflow",
+            Self::CannotTimeOutYourself => "cannot_time_out_yourself",
+            Self::RevealBeforeClaiming => "reveal_before_claiming",
+            Self::RematchBeforeClaiming => "rematch_before_claiming",
+            Self::TimeoutNotReached { .. } => "timeout_not_reached",
+            Self::WrongViewingKey => "wrong_viewing_key",
+            Self::HandProofNotAvailable => "hand_proof_not_available",
+            Self::HandNotFound => "hand_not_found",
+        }
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidBigBlind => write!(
+                f,
+                "Big blind must be an even number of credits, at least 2."
+            ),
+            Self::InvalidSeats { min, max } => {
+                write!(f, "A table must have between {} and {} seats.", min, max)
+            }
+            Self::InvalidActionTimeout => write!(f, "Action timeout must be at least 1 block."),
+            Self::InvalidBuyIn => write!(
+                f,
+                "The minimum buy-in must be at least 1 big blind and at most the maximum buy-in."
+            ),
+            Self::InvalidRake { max } => {
+                write!(f, "The rake can be at most {} basis points of a pot.", max)
+            }
+            Self::NotALobby => write!(
+                f,
+                "This contract hosts a single room, tables can't be added."
+            ),
+            Self::TableNotFound => write!(f, "There's no such table."),
+            Self::NoFunds => write!(f, "You must send funds to deposit."),
+            Self::WrongDenom { expected } => write!(f, "This table only accepts {}.", expected),
+            Self::BelowMinBuyIn { min } => {
+                write!(f, "Your stack must be at least {} credits.", min)
+            }
+            Self::AboveMaxBuyIn { max } => {
+                write!(f, "Your stack must be at most {} credits.", max)
+            }
+            Self::NothingToWithdraw => {
+                write!(
+                    f,
+                    "You are not a player, or you have no credits to withdraw."
+                )
+            }
+            Self::InvalidReceiveMsg => write!(
+                f,
+                "Send tokens with a Join, JoinTable or TopUp message to deposit them."
+            ),
+            Self::NotRakeCollector => write!(f, "Only the rake collector can collect the rake."),
+            Self::NothingToCollect => write!(f, "There's no rake to collect."),
+            Self::NotAPlayer => write!(f, "You are not a player at this table."),
+            Self::AlreadySeated => write!(f, "You are already sitting at a table, leave it first."),
+            Self::TableFull => write!(f, "Table is full."),
+            Self::NotEnoughPlayers => write!(
+                f,
+                "At least two players must be able to cover the big blind to play a hand."
+            ),
+            Self::CannotCoverBlinds => write!(
+                f,
+                "You don't have enough credits to cover the big blind, top up first."
+            ),
+            Self::HandNotStarted => write!(f, "Action hasn't started yet."),
+            Self::HandInProgress => write!(f, "You can't do that in the middle of a hand."),
+            Self::NotYourTurn => write!(f, "It's not your turn."),
+            Self::NotDealtIn => write!(
+                f,
+                "You were not dealt into this hand, wait for the next one."
+            ),
+            Self::InvalidCommitment => write!(
+                f,
+                "A commitment must be the 32 bytes SHA-256 hash of your secret."
+            ),
+            Self::ReusedCommitment => write!(
+                f,
+                "You already used this secret, commit to a fresh one for every hand."
+            ),
+            Self::WaitingForSecrets => {
+                write!(f, "Waiting for all players to reveal their secrets.")
+            }
+            Self::NothingToReveal => write!(f, "There's no secret to reveal right now."),
+            Self::AlreadyRevealed => write!(f, "You already revealed your secret."),
+            Self::SecretTooShort { min } => {
+                write!(f, "Your secret must be at least {} bytes long.", min)
+            }
+            Self::SecretMismatch => write!(f, "Your secret doesn't match your commitment."),
+            Self::CannotCheck => write!(f, "You cannot check, must Call, Raise or Fold."),
+            Self::NothingToCall => write!(
+                f,
+                "You cannot Call, your bet is bigger or equals to the other players' bets."
+            ),
+            Self::ZeroRaise => write!(f, "You cannot raise by 0 credits, Check or Call instead."),
+            Self::BelowMinRaise { min } => write!(
+                f,
+                "The minimum raise is {} credits, unless you go all-in.",
+                min
+            ),
+            Self::AboveMaxRaise { max } => write!(
+                f,
+                "This is a pot-limit table, the maximum raise is {} credits.",
+                max
+            ),
+            Self::FixedLimitRaise { size } => write!(
+                f,
+                "This is a fixed-limit table, raises on this street are exactly {} credits.",
+                size
+            ),
+            Self::BettingCapped => write!(
+                f,
+                "Betting is capped on this street, you can only Call or Fold."
+            ),
+            Self::EveryoneAllIn => write!(f, "Everyone else is all-in, you can only Call or Fold."),
+            Self::BettingNotReopened => write!(
+                f,
+                "A short all-in doesn't re-open the betting, you can only Call or Fold."
+            ),
+            Self::InsufficientFunds => {
+                write!(f, "You don't have enough credits to raise by that much.")
+            }
+            Self::Overflow => write!(f, "That many credits can't be accounted for."),
+            Self::CannotTimeOutYourself => {
+                write!(f, "It's your turn, you can't time yourself out.")
+            }
+            Self::RevealBeforeClaiming => {
+                write!(f, "Reveal your own secret before claiming a timeout.")
+            }
+            Self::RematchBeforeClaiming => {
+                write!(f, "Send Rematch before timing out the players who didn't.")
+            }
+            Self::TimeoutNotReached { blocks_left } => write!(
+                f,
+                "The player to act still has {} blocks left.",
+                blocks_left
+            ),
+            Self::WrongViewingKey => write!(
+                f,
+                "Wrong viewing key for this address or viewing key not set."
+            ),
+            Self::HandProofNotAvailable => {
+                write!(f, "The proof is only available after the hand is over.")
+            }
+            Self::HandNotFound => write!(f, "There's no such hand."),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    message: String,
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        let body = ErrorBody {
+            code: err.code(),
+            message: err.to_string(),
+        };
+        StdError::generic_err(serde_json::to_string(&body).unwrap_or(body.message))
+    }
+}
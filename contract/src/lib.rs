@@ -1,4 +1,9 @@
 pub mod contract;
+pub mod error;
+pub mod pokerstars;
+pub mod snip20;
+mod state;
+pub mod viewing_key;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
@@ -0,0 +1,285 @@
+//! Converts the hands returned by `GetHandHistory` to the PokerStars hand history text format,
+//! so they can be imported by poker tracking tools.
+
+use rs_poker::core::Card;
+
+use crate::contract::{BettingStructure, Event, HandHistory, Stage};
+
+struct Seat {
+    seat: u8,
+    name: String,
+    stack: u128,
+    folded: Option<&'static str>,
+    shown: Option<Vec<Card>>,
+    collected: u128,
+}
+
+fn card(card: &Card) -> String {
+    format!("{}{}", card.value.to_char(), card.suit.to_char())
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards.iter().map(card).collect::<Vec<String>>().join(" ")
+}
+
+/// The board before the turn or the river, followed by the card that was just dealt.
+fn last_card_dealt(board: &[Card]) -> String {
+    let (before, last) = board.split_at(board.len() - 1);
+    format!(" [{}] [{}]", cards(before), cards(last))
+}
+
+/// Block time (seconds since the epoch) as `yyyy/mm/dd hh:mm:ss`, in UTC.
+fn date_time(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{}/{:02}/{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Hand numbers must be unique across tables, so the table id goes in front of the game counter.
+fn hand_number(history: &HandHistory) -> String {
+    format!("{}{:010}", history.table_id, history.game_counter)
+}
+
+/// One hand in the PokerStars format. Players are named by their address and amounts are in
+/// credits, the hole cards of players that didn't go to showdown stay hidden.
+pub fn export_hand(history: &HandHistory) -> String {
+    let mut seats: Vec<Seat> = history
+        .seats
+        .iter()
+        .map(|s| Seat {
+            seat: s.seat,
+            name: s.address.to_string(),
+            stack: s.stack.u128(),
+            folded: None,
+            shown: None,
+            collected: 0,
+        })
+        .collect();
+    let name = |seats: &[Seat], seat: u8| -> String {
+        seats
+            .iter()
+            .find(|s| s.seat == seat)
+            .map(|s| s.name.clone())
+            .unwrap_or_default()
+    };
+
+    let game = match history.betting_structure {
+        BettingStructure::NoLimit => "No Limit",
+        BettingStructure::PotLimit => "Pot Limit",
+        BettingStructure::FixedLimit => "Limit",
+    };
+    let mut lines = vec![format!(
+        "PokerStars Hand #{}:  Hold'em {} ({}/{}) - {} UTC",
+        hand_number(history),
+        game,
+        history.small_blind,
+        history.big_blind,
+        date_time(history.proof.block_time)
+    )];
+
+    let mut button = None;
+    let mut blinds = vec![];
+    let mut street = "before Flop";
+    let mut street_bet = 0;
+    let mut showdown = false;
+    let pot_names: Vec<String> = (0..history.pots.len())
+        .map(|pot| match (history.pots.len(), pot) {
+            (1, _) => String::from("pot"),
+            (_, 0) => String::from("main pot"),
+            (_, side) => format!("side pot-{}", side),
+        })
+        .collect();
+
+    for action in history.actions.iter() {
+        match action {
+            Event::Deal { button: seat, .. } => {
+                button = Some(*seat);
+                lines.push(format!(
+                    "Table 'Secret Hold'em {}' {}-max Seat #{} is the button",
+                    history.table_id,
+                    history.max_seats,
+                    seat + 1
+                ));
+                for s in seats.iter() {
+                    lines.push(format!(
+                        "Seat {}: {} ({} in chips)",
+                        s.seat + 1,
+                        s.name,
+                        s.stack
+                    ));
+                }
+            }
+            Event::PostBlind { seat, amount } => {
+                let blind = if blinds.is_empty() { "small" } else { "big" };
+                blinds.push(*seat);
+                street_bet = amount.u128();
+                lines.push(format!(
+                    "{}: posts {} blind {}",
+                    name(&seats, *seat),
+                    blind,
+                    amount
+                ));
+            }
+            Event::Stage {
+                stage,
+                community_cards,
+            } => {
+                let (title, board) = match stage {
+                    Stage::PreFlop => ("HOLE CARDS", String::new()),
+                    Stage::Flop => ("FLOP", format!(" [{}]", cards(community_cards))),
+                    Stage::Turn => ("TURN", last_card_dealt(community_cards)),
+                    Stage::River => ("RIVER", last_card_dealt(community_cards)),
+                    _ => continue,
+                };
+                street = match stage {
+                    Stage::Flop => "on the Flop",
+                    Stage::Turn => "on the Turn",
+                    Stage::River => "on the River",
+                    _ => "before Flop",
+                };
+                if *stage != Stage::PreFlop {
+                    street_bet = 0;
+                }
+                lines.push(format!("*** {} ***{}", title, board));
+            }
+            Event::Check { seat } => lines.push(format!("{}: checks", name(&seats, *seat))),
+            Event::Call {
+                seat,
+                amount,
+                all_in,
+            } => lines.push(format!(
+                "{}: calls {}{}",
+                name(&seats, *seat),
+                amount,
+                if *all_in { " and is all-in" } else { "" }
+            )),
+            Event::Raise {
+                seat,
+                amount,
+                bet,
+                all_in,
+            } => {
+                let all_in = if *all_in { " and is all-in" } else { "" };
+                if street_bet == 0 {
+                    lines.push(format!(
+                        "{}: bets {}{}",
+                        name(&seats, *seat),
+                        amount,
+                        all_in
+                    ));
+                } else {
+                    lines.push(format!(
+                        "{}: raises {} to {}{}",
+                        name(&seats, *seat),
+                        amount,
+                        bet,
+                        all_in
+                    ));
+                }
+                street_bet = bet.u128();
+            }
+            Event::Fold { seat } => {
+                if let Some(s) = seats.iter_mut().find(|s| s.seat == *seat) {
+                    s.folded = Some(street);
+                }
+                lines.push(format!("{}: folds", name(&seats, *seat)));
+            }
+            Event::TimedOut { seat } => {
+                lines.push(format!("{} has timed out", name(&seats, *seat)))
+            }
+            Event::ReturnUncalledBet { seat, amount } => lines.push(format!(
+                "Uncalled bet ({}) returned to {}",
+                amount,
+                name(&seats, *seat)
+            )),
+            Event::Show { seat, hand } => {
+                if !showdown {
+                    showdown = true;
+                    lines.push(String::from("*** SHOW DOWN ***"));
+                }
+                if let Some(s) = seats.iter_mut().find(|s| s.seat == *seat) {
+                    s.shown = Some(hand.clone());
+                }
+                lines.push(format!("{}: shows [{}]", name(&seats, *seat), cards(hand)));
+            }
+            Event::WinPot { pot, seat, amount } => {
+                if let Some(s) = seats.iter_mut().find(|s| s.seat == *seat) {
+                    s.collected += amount.u128();
+                }
+                lines.push(format!(
+                    "{} collected {} from {}",
+                    name(&seats, *seat),
+                    amount,
+                    pot_names
+                        .get(*pot as usize)
+                        .map(String::as_str)
+                        .unwrap_or("pot")
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    lines.push(String::from("*** SUMMARY ***"));
+    let total: u128 = history.pots.iter().map(|pot| pot.amount.u128()).sum();
+    let mut total_line = format!("Total pot {}", total);
+    if history.pots.len() > 1 {
+        total_line.push_str(&format!(" Main pot {}.", history.pots[0].amount));
+        for (side, pot) in history.pots.iter().enumerate().skip(1) {
+            total_line.push_str(&format!(" Side pot-{} {}.", side, pot.amount));
+        }
+    }
+    let rake: u128 = history.pots.iter().map(|pot| pot.rake.u128()).sum();
+    total_line.push_str(&format!(" | Rake {}", rake));
+    lines.push(total_line);
+    if !history.community_cards.is_empty() {
+        lines.push(format!("Board [{}]", cards(&history.community_cards)));
+    }
+
+    for s in seats.iter() {
+        let mut line = format!("Seat {}: {}", s.seat + 1, s.name);
+        if button == Some(s.seat) {
+            line.push_str(" (button)");
+        }
+        if blinds.first() == Some(&s.seat) {
+            line.push_str(" (small blind)");
+        } else if blinds.get(1) == Some(&s.seat) {
+            line.push_str(" (big blind)");
+        }
+
+        match (s.folded, &s.shown) {
+            (Some(street), _) => line.push_str(&format!(" folded {}", street)),
+            (None, Some(hand)) if s.collected > 0 => line.push_str(&format!(
+                " showed [{}] and won ({})",
+                cards(hand),
+                s.collected
+            )),
+            (None, Some(hand)) => line.push_str(&format!(" showed [{}] and lost", cards(hand))),
+            (None, None) => line.push_str(&format!(" collected ({})", s.collected)),
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
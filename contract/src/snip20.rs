@@ -0,0 +1,53 @@
+//! The parts of the SNIP-20 token interface used by tables that play with a token:
+//! deposits come in through the token's `Receive` callback and payouts go out as `Transfer`s.
+
+use cosmwasm_std::{to_binary, CosmosMsg, HumanAddr, StdResult, Uint128, WasmMsg};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TokenMsg {
+    RegisterReceive {
+        code_hash: String,
+    },
+    Transfer {
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+}
+
+fn execute(token: &HumanAddr, token_code_hash: &str, msg: &TokenMsg) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.clone(),
+        callback_code_hash: token_code_hash.to_string(),
+        msg: to_binary(msg)?,
+        send: vec![],
+    }))
+}
+
+/// Asks the token to call `Receive` on this contract, which has `code_hash`,
+/// whenever tokens are sent to it.
+pub fn register_receive_msg(
+    token: &HumanAddr,
+    token_code_hash: &str,
+    code_hash: String,
+) -> StdResult<CosmosMsg> {
+    execute(
+        token,
+        token_code_hash,
+        &TokenMsg::RegisterReceive { code_hash },
+    )
+}
+
+pub fn transfer_msg(
+    token: &HumanAddr,
+    token_code_hash: &str,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    execute(
+        token,
+        token_code_hash,
+        &TokenMsg::Transfer { recipient, amount },
+    )
+}
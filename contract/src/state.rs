@@ -0,0 +1,194 @@
+//! Typed access to everything the contract keeps in storage. Each kind of record lives in its
+//! own namespace, keyed by table id, then game counter for records of a single hand.
+
+use cosmwasm_std::{HumanAddr, ReadonlyStorage, StdResult, Storage};
+use cosmwasm_storage::{bucket, bucket_read, prefixed, prefixed_read, singleton, singleton_read};
+use rs_poker::core::{Card, Suit, Value};
+
+use crate::contract::{HandHistory, HandProof, Lobby, Table};
+use crate::error::ContractError;
+
+const LOBBY: &[u8] = b"lobby";
+const TABLES: &[u8] = b"tables";
+const PLAYER_TABLES: &[u8] = b"player_tables";
+const DECKS: &[u8] = b"decks";
+const PROOFS: &[u8] = b"proofs";
+const HISTORY: &[u8] = b"history";
+const COMMITMENTS: &[u8] = b"commitments";
+const SECRETS: &[u8] = b"secrets";
+const VIEWING_KEYS: &[u8] = b"viewing_keys";
+const PRNG_SEED: &[u8] = b"prng_seed";
+const RAKED_TABLES: &[u8] = b"raked_tables";
+
+fn hand_key(table_id: u64, game_counter: u64) -> Vec<u8> {
+    let mut key = table_id.to_be_bytes().to_vec();
+    key.extend(&game_counter.to_be_bytes());
+    key
+}
+
+pub(crate) fn load_lobby<S: ReadonlyStorage>(storage: &S) -> StdResult<Lobby> {
+    singleton_read(storage, LOBBY).load()
+}
+
+pub(crate) fn save_lobby<S: Storage>(storage: &mut S, lobby: &Lobby) -> StdResult<()> {
+    singleton(storage, LOBBY).save(lobby)
+}
+
+pub(crate) fn load_table<S: ReadonlyStorage>(storage: &S, table_id: u64) -> StdResult<Table> {
+    match bucket_read(TABLES, storage).may_load(&table_id.to_be_bytes())? {
+        Some(table) => Ok(table),
+        None => Err(ContractError::TableNotFound.into()),
+    }
+}
+
+pub(crate) fn save_table<S: Storage>(storage: &mut S, table: &Table) -> StdResult<()> {
+    bucket(TABLES, storage).save(&table.id.to_be_bytes(), table)
+}
+
+/// The ids of the tables holding rake the collector hasn't collected yet, so collecting doesn't
+/// have to go through every table ever created.
+pub(crate) fn load_raked_tables<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<u64>> {
+    Ok(singleton_read(storage, RAKED_TABLES)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub(crate) fn save_raked_tables<S: Storage>(storage: &mut S, table_ids: &[u64]) -> StdResult<()> {
+    singleton(storage, RAKED_TABLES).save(&table_ids.to_vec())
+}
+
+/// The id of the table `player` is sitting at, players sit at one table at a time.
+pub(crate) fn player_table_id<S: ReadonlyStorage>(
+    storage: &S,
+    player: &HumanAddr,
+) -> StdResult<Option<u64>> {
+    bucket_read(PLAYER_TABLES, storage).may_load(player.as_str().as_bytes())
+}
+
+/// The table `player` is sitting at.
+pub(crate) fn player_table<S: ReadonlyStorage>(
+    storage: &S,
+    player: &HumanAddr,
+) -> StdResult<Table> {
+    match player_table_id(storage, player)? {
+        Some(table_id) => load_table(storage, table_id),
+        None => Err(ContractError::NotAPlayer.into()),
+    }
+}
+
+pub(crate) fn seat_player<S: Storage>(
+    storage: &mut S,
+    player: &HumanAddr,
+    table_id: u64,
+) -> StdResult<()> {
+    bucket(PLAYER_TABLES, storage).save(player.as_str().as_bytes(), &table_id)
+}
+
+pub(crate) fn unseat_player<S: Storage>(storage: &mut S, player: &HumanAddr) {
+    bucket::<S, u64>(PLAYER_TABLES, storage).remove(player.as_str().as_bytes())
+}
+
+/// One byte per card: value * 4 + suit, with values from Two (0) to Ace (12)
+/// and suits Spade (0), Club (1), Heart (2), Diamond (3).
+pub(crate) fn deck_to_bytes(deck: &[Card]) -> Vec<u8> {
+    deck.iter()
+        .map(|card| card.value as u8 * 4 + card.suit as u8)
+        .collect()
+}
+
+fn deck_from_bytes(bytes: &[u8]) -> Vec<Card> {
+    bytes
+        .iter()
+        .map(|byte| Card {
+            value: Value::from_u8(byte / 4),
+            suit: Suit::from_u8(byte % 4),
+        })
+        .collect()
+}
+
+/// The deck of the hand in progress at `table_id`, stored with `deck_to_bytes`.
+pub(crate) fn load_deck<S: ReadonlyStorage>(storage: &S, table_id: u64) -> StdResult<Vec<Card>> {
+    match prefixed_read(DECKS, storage).get(&table_id.to_be_bytes()) {
+        Some(bytes) => Ok(deck_from_bytes(&bytes)),
+        None => Err(ContractError::HandNotStarted.into()),
+    }
+}
+
+pub(crate) fn save_deck<S: Storage>(storage: &mut S, table_id: u64, deck: &[Card]) {
+    prefixed(DECKS, storage).set(&table_id.to_be_bytes(), &deck_to_bytes(deck));
+}
+
+pub(crate) fn load_proof<S: ReadonlyStorage>(
+    storage: &S,
+    table_id: u64,
+    game_counter: u64,
+) -> StdResult<Option<HandProof>> {
+    bucket_read(PROOFS, storage).may_load(&hand_key(table_id, game_counter))
+}
+
+pub(crate) fn save_proof<S: Storage>(
+    storage: &mut S,
+    table_id: u64,
+    proof: &HandProof,
+) -> StdResult<()> {
+    bucket(PROOFS, storage).save(&hand_key(table_id, proof.game_counter), proof)
+}
+
+pub(crate) fn load_history<S: ReadonlyStorage>(
+    storage: &S,
+    table_id: u64,
+    game_counter: u64,
+) -> StdResult<Option<HandHistory>> {
+    bucket_read(HISTORY, storage).may_load(&hand_key(table_id, game_counter))
+}
+
+pub(crate) fn save_history<S: Storage>(storage: &mut S, history: &HandHistory) -> StdResult<()> {
+    let key = hand_key(history.table_id, history.game_counter);
+    bucket(HISTORY, storage).save(&key, history)
+}
+
+/// The SHA-256 hash of the secret `player` will reveal for their next hand.
+pub(crate) fn load_commitment<S: ReadonlyStorage>(
+    storage: &S,
+    player: &HumanAddr,
+) -> Option<Vec<u8>> {
+    prefixed_read(COMMITMENTS, storage).get(player.as_str().as_bytes())
+}
+
+pub(crate) fn save_commitment<S: Storage>(storage: &mut S, player: &HumanAddr, commitment: &[u8]) {
+    prefixed(COMMITMENTS, storage).set(player.as_str().as_bytes(), commitment);
+}
+
+/// The secret `player` revealed for the hand in progress.
+pub(crate) fn load_secret<S: ReadonlyStorage>(storage: &S, player: &HumanAddr) -> Option<Vec<u8>> {
+    prefixed_read(SECRETS, storage).get(player.as_str().as_bytes())
+}
+
+pub(crate) fn save_secret<S: Storage>(storage: &mut S, player: &HumanAddr, secret: &[u8]) {
+    prefixed(SECRETS, storage).set(player.as_str().as_bytes(), secret);
+}
+
+pub(crate) fn remove_secret<S: Storage>(storage: &mut S, player: &HumanAddr) {
+    prefixed(SECRETS, storage).remove(player.as_str().as_bytes());
+}
+
+/// The SHA-256 hash of the viewing key `player` set, the key itself isn't stored.
+pub(crate) fn load_viewing_key<S: ReadonlyStorage>(
+    storage: &S,
+    player: &HumanAddr,
+) -> Option<Vec<u8>> {
+    prefixed_read(VIEWING_KEYS, storage).get(player.as_str().as_bytes())
+}
+
+pub(crate) fn save_viewing_key<S: Storage>(storage: &mut S, player: &HumanAddr, hashed_key: &[u8]) {
+    prefixed(VIEWING_KEYS, storage).set(player.as_str().as_bytes(), hashed_key);
+}
+
+/// The seed viewing keys are generated from, set at init and evolved with every key.
+pub(crate) fn load_prng_seed<S: ReadonlyStorage>(storage: &S) -> Vec<u8> {
+    storage.get(PRNG_SEED).unwrap_or_default()
+}
+
+pub(crate) fn save_prng_seed<S: Storage>(storage: &mut S, seed: &[u8]) {
+    storage.set(PRNG_SEED, seed);
+}
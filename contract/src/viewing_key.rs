@@ -0,0 +1,51 @@
+use cosmwasm_std::{Binary, Env, HumanAddr, ReadonlyStorage, Storage};
+use sha2::{Digest, Sha256};
+
+use crate::state::{load_prng_seed, load_viewing_key, save_prng_seed, save_viewing_key};
+
+pub const VIEWING_KEY_PREFIX: &str = "api_key_";
+
+/// Generates a new viewing key from the contract's seed, the caller's entropy and the block,
+/// and evolves the seed so the next key doesn't depend on this one alone.
+pub fn create_viewing_key<S: Storage>(storage: &mut S, env: &Env, entropy: &str) -> String {
+    let mut seed_input = load_prng_seed(storage);
+    seed_input.extend(env.message.sender.as_str().as_bytes());
+    seed_input.extend(&env.block.height.to_be_bytes());
+    seed_input.extend(&env.block.time.to_be_bytes());
+    seed_input.extend(entropy.as_bytes());
+    let seed = Sha256::digest(&seed_input);
+
+    save_prng_seed(storage, seed.as_slice());
+
+    let key = Sha256::digest(&[seed.as_slice(), &b"viewing_key"[..]].concat());
+    format!("{}{}", VIEWING_KEY_PREFIX, Binary(key.to_vec()).to_base64())
+}
+
+/// Only a hash of the key is stored.
+pub fn set_viewing_key<S: Storage>(storage: &mut S, player: &HumanAddr, key: &str) {
+    save_viewing_key(storage, player, Sha256::digest(key.as_bytes()).as_slice());
+}
+
+pub fn check_viewing_key<S: ReadonlyStorage>(storage: &S, player: &HumanAddr, key: &str) -> bool {
+    let hashed_key = Sha256::digest(key.as_bytes());
+    match load_viewing_key(storage, player) {
+        Some(stored) => ct_slice_compare(&stored, hashed_key.as_slice()),
+        None => {
+            // compare anyway so a missing key takes as long as a wrong one
+            ct_slice_compare(&[0u8; 32], hashed_key.as_slice());
+            false
+        }
+    }
+}
+
+/// Compares in constant time, the result doesn't leak how many leading bytes match.
+fn ct_slice_compare(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}